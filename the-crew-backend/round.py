@@ -36,6 +36,7 @@ class Solve:
     success: bool
     result: bool
     duration: int
+    error: Optional[dict] = None
 
 
 COMMANDER_CARD = Card("R", 4)
@@ -123,7 +124,7 @@ class Round:
 
     def addSolverResult(self, id, result):
         self.solves.append(
-            Solve(id, result["success"], result["result"], result["duration"]))
+            Solve(id, result["success"], result["result"], result["duration"], result.get("error")))
 
     def toJson(self):
         def toPlayer(playerNum: int, player: PlayerState):
@@ -186,6 +187,7 @@ class Round:
                     "success": solve.success,
                     "result": solve.result,
                     "duration": solve.duration,
+                    "error": solve.error,
                 } for solve in self.solves]
             }
         }
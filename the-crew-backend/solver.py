@@ -21,28 +21,14 @@ class Solver:
         if SOLVER_ENDPOINT is None:
             return
 
-        data = self._stateToInput(state)
+        # The solver reads the round as the frontend sees it, and works out the rest itself.
+        data = state.toJson()
+        # Past solves grow with every request and the solver doesn't read them.
+        del data["solverPage"]
         threading.Thread(target=sendRequest, args=(
             self.nextId, data, callback)).start()
         self.nextId += 1
 
-    def _stateToInput(self, state: Round):
-        def cardToStr(card):
-            return f"{card.suit}{card.value}" if card is not None else None
-
-        return {
-            "hands": [[
-                cardToStr(card) for card in player.hand
-            ]for player in state.players],
-            "tasks": [{
-                "task_type": task.type,
-                "order": task.order,
-                "card": cardToStr(task.card),
-                "player_num": task.playerNum,
-            } for id, task in state.objectives.items()],
-            "curr_leader": state.tricks[-1].leadPlayerNum
-        }
-
 
 def sendRequest(id, data, callback):
     if SOLVER_ENDPOINT is None:
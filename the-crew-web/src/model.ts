@@ -62,6 +62,12 @@ export type Solve = {
     success: boolean;
     result: boolean;
     duration: number;
+    error?: SolveError | null;
+};
+
+export type SolveError = {
+    code: string;
+    message: string;
 };
 
 export type Player = {
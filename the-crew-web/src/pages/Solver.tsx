@@ -38,6 +38,9 @@ function result(solve: Solve): string {
     else if (solve.success && !solve.result) {
         return "Not winnable";
     }
+    else if (solve.error) {
+        return `Computation failed: ${solve.error.message}`;
+    }
     else {
         return "Computation failed";
     }
@@ -0,0 +1,478 @@
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::card::Card;
+use crate::cli::{
+    card_to_str, parse_card, to_game_state, to_state, Json, ParseError, State, TaskDTO,
+    SCHEMA_VERSION,
+};
+use crate::player::{PlayerIndex, NUM_PLAYERS};
+use crate::record::{GameRecord, Metadata};
+
+#[derive(Deserialize)]
+struct CardDTO {
+    suit: String,
+    value: u8,
+}
+
+#[derive(Deserialize)]
+struct PlayerDTO {
+    num: PlayerIndex,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AppStateDTO {
+    objective_page: ObjectivePageDTO,
+    tricks_page: TricksPageDTO,
+}
+
+#[derive(Deserialize)]
+struct ObjectivePageDTO {
+    tasks: Vec<AppTaskDTO>,
+}
+
+#[derive(Deserialize)]
+struct AppTaskDTO {
+    id: u32,
+    #[serde(rename = "type")]
+    task_type: String,
+    order: u8,
+    card: Option<CardDTO>,
+    player: Option<PlayerDTO>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TricksPageDTO {
+    tricks: Vec<AppTrickDTO>,
+    held_cards: Vec<Vec<CardDTO>>,
+}
+
+#[derive(Deserialize)]
+struct AppTrickDTO {
+    turns: Vec<TurnDTO>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TurnDTO {
+    card: Option<CardDTO>,
+    is_leader: bool,
+}
+
+#[derive(Deserialize)]
+struct RoundDTO {
+    players: Vec<PlayerStateDTO>,
+    /// By task id, which JSON writes as a string.
+    objectives: BTreeMap<u32, RoundTaskDTO>,
+    tricks: Vec<RoundTrickDTO>,
+}
+
+#[derive(Deserialize)]
+struct PlayerStateDTO {
+    hand: Vec<CardDTO>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoundTaskDTO {
+    #[serde(rename = "type")]
+    task_type: String,
+    order: u8,
+    card: Option<CardDTO>,
+    player_num: Option<PlayerIndex>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoundTrickDTO {
+    turns: Vec<Option<CardDTO>>,
+    lead_player_num: PlayerIndex,
+}
+
+/// Just enough of a document to tell the backend's shapes apart.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShapeDTO {
+    tricks_page: Option<IgnoredAny>,
+    objectives: Option<IgnoredAny>,
+}
+
+/// A round as the backend keeps it, whichever shape it came in.
+struct Round {
+    /// The cards each player still holds.
+    held: Vec<Vec<Card>>,
+    tasks: Vec<TaskDTO>,
+    /// Each trick's leader and the card each seat played in it, by seat.
+    tricks: Vec<(PlayerIndex, Vec<Option<Card>>)>,
+}
+
+/// Reads the backend's `/api/appstate` document, or the backend's `Round` written out as JSON,
+/// if `input` is one of them. The game is the one at the current point of the round: the cards
+/// still held, the tasks not yet done, the tricks played, who leads and any cards already played
+/// in the trick in progress, as `current_trick`. If a task was already failed, the trick it was
+/// failed in comes with it.
+pub(crate) fn read_backend(json: &Json) -> Option<Result<(State, Option<u8>), ParseError>> {
+    let shape = json.read::<ShapeDTO>().ok()?;
+    let round = match (shape.tricks_page, shape.objectives) {
+        (Some(_), _) => json.read().and_then(from_appstate),
+        (None, Some(_)) => json.read().and_then(from_round),
+        (None, None) => return None,
+    };
+    Some(round.and_then(|round| to_current_state(&round)))
+}
+
+fn from_appstate(appstate: AppStateDTO) -> Result<Round, ParseError> {
+    let tasks = appstate
+        .objective_page
+        .tasks
+        .into_iter()
+        .map(|t| task(t.id, t.task_type, t.order, t.card, t.player.map(|p| p.num)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let tricks = appstate
+        .tricks_page
+        .tricks
+        .into_iter()
+        .enumerate()
+        .map(|(i, trick)| {
+            let leader = trick
+                .turns
+                .iter()
+                .position(|t| t.is_leader)
+                .ok_or_else(|| invalid(format!("trick {} has no leader", i + 1)))?;
+            let turns = trick
+                .turns
+                .into_iter()
+                .map(|t| t.card.as_ref().map(card).transpose())
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((leader as PlayerIndex, turns))
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    Ok(Round {
+        held: cards(&appstate.tricks_page.held_cards)?,
+        tasks,
+        tricks,
+    })
+}
+
+fn from_round(round: RoundDTO) -> Result<Round, ParseError> {
+    let tasks = round
+        .objectives
+        .into_iter()
+        .map(|(id, t)| task(id, t.task_type, t.order, t.card, t.player_num))
+        .collect::<Result<Vec<_>, _>>()?;
+    let tricks = round
+        .tricks
+        .into_iter()
+        .map(|trick| {
+            let turns = trick
+                .turns
+                .iter()
+                .map(|c| c.as_ref().map(card).transpose())
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((trick.lead_player_num, turns))
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    let hands = round
+        .players
+        .into_iter()
+        .map(|p| p.hand)
+        .collect::<Vec<_>>();
+    Ok(Round {
+        held: cards(&hands)?,
+        tasks,
+        tricks,
+    })
+}
+
+/// A task of the round, which the backend lets players add before choosing its card and player.
+fn task(
+    id: u32,
+    task_type: String,
+    order: u8,
+    card: Option<CardDTO>,
+    player: Option<PlayerIndex>,
+) -> Result<TaskDTO, ParseError> {
+    let task_card = card.ok_or(ParseError::IncompleteTask {
+        task: id,
+        field: "card",
+    })?;
+    let player_num = player.ok_or(ParseError::IncompleteTask {
+        task: id,
+        field: "player",
+    })?;
+    Ok(TaskDTO {
+        task_type,
+        order,
+        card: card_to_str(self::card(&task_card)?),
+        player_num,
+        earliest: None,
+        latest: None,
+    })
+}
+
+/// Plays the round's tricks from the deal they were played from, so that done tasks are dropped
+/// and the leader follows the winners, and checks every card on the way. Also gives the trick a
+/// task was failed in, if one was.
+fn to_current_state(round: &Round) -> Result<(State, Option<u8>), ParseError> {
+    let Some((first_leader, _)) = round.tricks.first() else {
+        return Err(invalid(
+            "no tricks; the first one gives the leader".to_string(),
+        ));
+    };
+
+    let mut hands = round.held.clone();
+    let mut tricks = vec![];
+    let mut open = false;
+    for (i, (leader, turns)) in round.tricks.iter().enumerate() {
+        if turns.len() > NUM_PLAYERS {
+            return Err(invalid(format!("trick {} has too many turns", i + 1)));
+        }
+        let mut cards = vec![];
+        for j in 0..NUM_PLAYERS {
+            let seat = (*leader as usize + j) % NUM_PLAYERS;
+            match turns.get(seat).copied().flatten() {
+                Some(_) if open => {
+                    return Err(invalid(format!(
+                        "player {} played in trick {} before it was their turn",
+                        seat,
+                        i + 1
+                    )))
+                }
+                Some(card) => {
+                    cards.push(card);
+                    if let Some(hand) = hands.get_mut(seat) {
+                        hand.push(card);
+                    }
+                }
+                None => open = true,
+            }
+        }
+        if !cards.is_empty() {
+            tricks.push(cards);
+        }
+    }
+
+    let deal = State {
+        version: SCHEMA_VERSION,
+        hands: hands
+            .iter()
+            .map(|h| h.iter().map(|c| card_to_str(*c)).collect())
+            .collect(),
+        tasks: round.tasks.clone(),
+        curr_leader: *first_leader,
+        tricks_played: 0,
+        mission: None,
+        current_trick: vec![],
+        tricks: vec![],
+        winners: vec![],
+    };
+    let record = GameRecord {
+        metadata: Metadata::default(),
+        deal: to_game_state(&deal)?,
+        communications: vec![],
+        tricks,
+    };
+    let (tricks, cards) = record.end();
+    let position = record
+        .position(tricks, cards)
+        .map_err(|e| invalid(e.to_string()))?;
+    let state = State {
+        current_trick: position
+            .current_trick
+            .iter()
+            .map(|c| card_to_str(*c))
+            .collect(),
+        ..to_state(&position.state, None)
+    };
+    Ok((state, position.failed_trick))
+}
+
+fn cards(hands: &[Vec<CardDTO>]) -> Result<Vec<Vec<Card>>, ParseError> {
+    hands
+        .iter()
+        .map(|hand| hand.iter().map(card).collect())
+        .collect()
+}
+
+fn card(card: &CardDTO) -> Result<Card, ParseError> {
+    parse_card(&format!("{}{}", card.suit, card.value))
+}
+
+fn invalid(message: String) -> ParseError {
+    ParseError::AppState(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::card::Card::*;
+    use crate::cli::{parse, parse_position};
+
+    fn card(name: &str) -> Value {
+        json!({ "suit": &name[..1], "value": name[1..].parse::<u8>().unwrap() })
+    }
+
+    fn cards(names: &[&str]) -> Value {
+        names.iter().map(|c| card(c)).collect()
+    }
+
+    /// `/api/appstate` as the backend writes it, with tasks of (type, order, card, player).
+    fn appstate(
+        held: [&[&str]; 4],
+        tasks: &[(&str, u8, Option<&str>, Option<u8>)],
+        tricks: &[(usize, [Option<&str>; 4])],
+    ) -> String {
+        let player = |num: usize| json!({ "num": num, "name": format!("Player {}", num + 1) });
+        let held = held.map(cards);
+        json!({
+            "handPage": { "heldCards": held },
+            "objectivePage": {
+                "tasks": tasks.iter().enumerate().map(|(i, (task_type, order, c, p))| json!({
+                    "id": i + 1,
+                    "type": task_type,
+                    "order": order,
+                    "card": c.map(card),
+                    "player": p.map(|p| player(p as usize)),
+                })).collect::<Vec<_>>(),
+            },
+            "tricksPage": {
+                "tricks": tricks.iter().map(|(leader, turns)| json!({
+                    "turns": turns.iter().enumerate().map(|(seat, c)| json!({
+                        "player": player(seat),
+                        "card": c.map(card),
+                        "isLeader": seat == *leader,
+                        "isWinner": false,
+                        "isNextToPlay": false,
+                    })).collect::<Vec<_>>(),
+                })).collect::<Vec<_>>(),
+                "heldCards": held,
+            },
+            "solverPage": { "solves": [] },
+        })
+        .to_string()
+    }
+
+    /// The toy deal after P1 has won B9 B2 Y3 B4 and led B1.
+    fn second_trick(tasks: &[(&str, u8, Option<&str>, Option<u8>)]) -> String {
+        appstate(
+            [&[], &["Y2"], &["Y7"], &["Y4"]],
+            tasks,
+            &[
+                (0, [Some("B9"), Some("B2"), Some("Y3"), Some("B4")]),
+                (0, [Some("B1"), None, None, None]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_appstate() {
+        let input = second_trick(&[("absolute", 1, Some("Y2"), Some(0))]);
+        let position = parse_position(&input).unwrap();
+        assert_eq!(position.current_trick, [B1]);
+        assert_eq!(position.failed_trick, None);
+        let state = position.state;
+        assert_eq!(state.curr_leader(), 0);
+        assert_eq!(state.tasks().tricks_played(), 1);
+        assert_eq!(state.hands()[0].cards(), []);
+        assert_eq!(state.hands()[1].cards(), [Y2]);
+        assert_eq!(
+            parse(&input).unwrap_err(),
+            ParseError::UnexpectedField("current_trick")
+        );
+
+        // A new round: no cards played, and the first trick is led by whoever holds R4.
+        let input = appstate(
+            [&["B1", "R4"], &["B2", "Y2"], &["Y3", "Y7"], &["B4", "Y4"]],
+            &[("anytime", 0, Some("Y2"), Some(2))],
+            &[(0, [None; 4])],
+        );
+        let state = parse(&input).unwrap();
+        assert_eq!(state.tasks().tricks_played(), 0);
+        assert_eq!(state.hands()[0].cards(), [B1, R4]);
+    }
+
+    #[test]
+    fn test_round() {
+        let round = json!({
+            "taskId": 2,
+            "players": [
+                { "name": "Ann", "hand": [] },
+                { "name": "Bo", "hand": cards(&["Y2"]) },
+                { "name": "Cy", "hand": cards(&["Y7"]) },
+                { "name": "Di", "hand": cards(&["Y4"]) },
+            ],
+            "objectives": {
+                "1": { "type": "absolute", "order": 1, "card": card("Y2"), "playerNum": 0 },
+            },
+            "tricks": [
+                {
+                    "turns": [card("B9"), card("B2"), card("Y3"), card("B4")],
+                    "leadPlayerNum": 0, "nextTurnPlayerNum": null, "winnerPlayerNum": 0,
+                },
+                {
+                    "turns": [card("B1"), null, null, null],
+                    "leadPlayerNum": 0, "nextTurnPlayerNum": 1,
+                },
+            ],
+            "solves": [],
+        });
+        let appstate = second_trick(&[("absolute", 1, Some("Y2"), Some(0))]);
+        assert_eq!(
+            parse_position(&round.to_string()),
+            parse_position(&appstate)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |input: &str| parse_position(input).unwrap_err();
+        let unfinished = second_trick(&[
+            ("absolute", 1, Some("Y2"), Some(0)),
+            ("relative", 1, None, Some(1)),
+        ]);
+        assert_eq!(
+            error(&unfinished),
+            ParseError::IncompleteTask {
+                task: 2,
+                field: "card"
+            }
+        );
+        assert_eq!(error(&unfinished).to_string(), "task 2 has no card yet");
+        assert_eq!(error(&unfinished).code(), "incomplete_task");
+        let unassigned = second_trick(&[("anytime", 0, Some("Y2"), None)]);
+        assert_eq!(error(&unassigned).to_string(), "task 1 has no player yet");
+
+        // P1 won B2, which was P2's to win.
+        let lost = second_trick(&[("anytime", 0, Some("B2"), Some(1))]);
+        let position = parse_position(&lost).unwrap();
+        assert_eq!(position.failed_trick, Some(1));
+        assert_eq!(position.current_trick, [B1]);
+        assert_eq!(position.solve(Duration::from_secs(5)), Some(false));
+        assert_eq!(parse(&lost).unwrap_err(), ParseError::GameLost(1));
+
+        let skipped = appstate(
+            [&["B1", "B9"], &["B2", "Y2"], &["Y7"], &["B4", "Y4"]],
+            &[],
+            &[(0, [None, None, Some("Y3"), None])],
+        );
+        assert_eq!(
+            error(&skipped).to_string(),
+            "invalid appstate: player 2 played in trick 1 before it was their turn"
+        );
+        let not_following = appstate(
+            [&["B1"], &["B2"], &["Y3", "Y7"], &["B4", "Y4"]],
+            &[],
+            &[(0, [Some("B9"), Some("Y2"), None, None])],
+        );
+        assert_eq!(
+            error(&not_following).to_string(),
+            "invalid appstate: Y2 can't be played in trick 1"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Command line arguments of the form `<command> --name value --switch ...`.
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    command: Option<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut args = args.into_iter().peekable();
+        let command = args.next_if(|a| !a.starts_with("--"));
+
+        let mut options = HashMap::new();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unexpected argument `{}`", arg))?;
+            let value = args.next_if(|a| !a.starts_with("--"));
+            options.insert(name.to_string(), value.unwrap_or_default());
+        }
+
+        Ok(Args { command, options })
+    }
+
+    pub fn command(&self) -> Option<&str> {
+        self.command.as_deref()
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.options
+            .get(name)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| format!("invalid value `{}` for --{}", v, name))
+            })
+            .transpose()
+    }
+
+    pub fn get_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        Ok(self.get(name)?.unwrap_or(default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Args, String> {
+        Args::parse(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = args(&["generate", "--seed", "12", "--verbose", "--tasks", "3"]).unwrap();
+        assert_eq!(parsed.command(), Some("generate"));
+        assert_eq!(parsed.get::<u64>("seed"), Ok(Some(12)));
+        assert_eq!(parsed.get::<u8>("tasks"), Ok(Some(3)));
+        assert_eq!(parsed.get::<u8>("players"), Ok(None));
+        assert_eq!(parsed.get_or::<u8>("players", 4), Ok(4));
+        assert!(parsed.has("verbose"));
+        assert!(!parsed.has("players"));
+    }
+
+    #[test]
+    fn test_parse_no_command() {
+        let parsed = args(&[]).unwrap();
+        assert_eq!(parsed.command(), None);
+
+        let parsed = args(&["--seed", "1"]).unwrap();
+        assert_eq!(parsed.command(), None);
+        assert!(parsed.has("seed"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(args(&["generate", "stray"]).is_err());
+        assert!(args(&["generate", "--seed", "abc"])
+            .unwrap()
+            .get::<u64>("seed")
+            .is_err());
+    }
+}
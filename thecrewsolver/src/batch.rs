@@ -0,0 +1,145 @@
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+use crate::cli::{parse_batch_line, RunOutput};
+use crate::hint::solve_position;
+use crate::parallel::parallel_stream;
+use crate::solver::{cache_limit_per_thread, Search};
+
+pub struct BatchOptions {
+    /// How many games are solved at once.
+    pub threads: usize,
+    /// How long each game may take.
+    pub timeout: Option<Duration>,
+    /// How many lost positions the games being solved at once remember between them.
+    pub cache_limit: usize,
+}
+
+/// Solves one line of batch input and returns the line to write for it, which is what `solve`
+/// prints along with the line's `id`.
+pub fn solve_line(line: &str, options: &BatchOptions) -> String {
+    let (id, position) = parse_batch_line(line);
+    let output = match position {
+        Ok(position) => {
+            let start = Instant::now();
+            let mut search = Search::new(options.timeout.map(|t| start + t))
+                .with_cache_limit(cache_limit_per_thread(options.cache_limit, options.threads));
+            let result = match position.failed_trick {
+                Some(_) => Some(false),
+                None => solve_position(&mut search, &position.state, &position.current_trick),
+            };
+            RunOutput::new(
+                result.is_some(),
+                result.unwrap_or(false),
+                start.elapsed().as_millis(),
+            )
+        }
+        Err(error) => RunOutput::invalid(&error),
+    };
+    output.with_id(id).to_json()
+}
+
+/// Solves every non-empty line of `input` until it ends, writing one line to `output` for each
+/// as soon as it is solved, so the results come in the order they finish and callers match them
+/// up by `id`. Reading stops at the first line that isn't UTF-8. Returns how many games were
+/// solved.
+pub fn solve_batch<R, W>(input: R, output: &mut W, options: &BatchOptions) -> io::Result<usize>
+where
+    R: BufRead + Send,
+    W: Write,
+{
+    let lines = input
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty());
+
+    let mut solved = 0;
+    let mut error = None;
+    parallel_stream(
+        lines,
+        options.threads,
+        |line| solve_line(&line, options),
+        |result| {
+            if error.is_some() {
+                return;
+            }
+            match writeln!(output, "{}", result).and_then(|_| output.flush()) {
+                Ok(()) => solved += 1,
+                Err(e) => error = Some(e),
+            }
+        },
+    );
+    match error {
+        Some(e) => Err(e),
+        None => Ok(solved),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::cli::format_state;
+    use crate::fixtures::toy_state;
+    use crate::tasks::Task;
+    use serde_json::Value;
+
+    fn options() -> BatchOptions {
+        BatchOptions {
+            threads: 2,
+            timeout: Some(Duration::from_secs(5)),
+            cache_limit: 1000,
+        }
+    }
+
+    /// The game as a batch line with `id` added in front.
+    fn line(id: &str, task: Task) -> String {
+        format!(
+            "{{\"id\":{},{}",
+            id,
+            &format_state(&toy_state(task), None)[1..]
+        )
+    }
+
+    #[test]
+    fn test_solve_line() {
+        assert_eq!(
+            solve_line(&line("\"a\"", Task::new(0, Y2)), &options())
+                .replace(|c: char| c.is_ascii_digit(), ""),
+            r#"{"version":,"id":"a","success":true,"result":true,"duration":}"#
+        );
+        assert_eq!(
+            solve_line("{\"id\":7,\"hands\":[]}", &options()),
+            r#"{"version":2,"id":7,"success":false,"result":false,"duration":0,"error":{"code":"invalid_json","message":"invalid JSON at line 1 column 19: missing field `tasks`"}}"#
+        );
+        assert!(solve_line("not json", &options()).starts_with(r#"{"version":2,"success":false"#));
+
+        // A trick in progress is read as `solve` reads it: P0 led B9 and wins Y2 next trick.
+        let in_progress = r#"{"id":4,"hands":[["B1"],["B2","Y2"],["Y3","Y7"],["B4","Y4"]],"tasks":[{"task_type":"anytime","card":"Y2","player_num":0}],"curr_leader":0,"current_trick":["B9"]}"#;
+        assert!(solve_line(in_progress, &options())
+            .starts_with(r#"{"version":2,"id":4,"success":true,"result":true"#));
+    }
+
+    #[test]
+    fn test_solve_batch() {
+        let input = [
+            line("1", Task::new(0, Y2)),
+            String::new(),
+            line("2", Task::new(1, Y7)),
+            line("3", Task::new(0, B9)),
+        ]
+        .join("\n");
+        let mut output = vec![];
+        let solved = solve_batch(input.as_bytes(), &mut output, &options()).unwrap();
+        assert_eq!(solved, 3);
+
+        let mut results = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str::<Value>(l).unwrap())
+            .map(|v| (v["id"].as_u64().unwrap(), v["result"].as_bool().unwrap()))
+            .collect::<Vec<_>>();
+        results.sort();
+        assert_eq!(results, vec![(1, true), (2, false), (3, true)]);
+    }
+}
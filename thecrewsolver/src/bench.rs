@@ -0,0 +1,69 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::estimate::{DurationSummary, Outcome, Sample};
+use crate::solver::{GameState, Search};
+
+#[derive(Serialize, Debug)]
+pub struct BenchReport {
+    pub runs: usize,
+    /// `None` if any run timed out.
+    pub winnable: Option<bool>,
+    /// Positions searched by one run.
+    pub nodes: u64,
+    pub duration: DurationSummary,
+}
+
+impl BenchReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Solves the same position `runs` times from scratch and summarises how long it took.
+pub fn bench(state: &GameState, runs: usize, timeout: Duration) -> BenchReport {
+    let mut winnable = Some(false);
+    let mut nodes = 0;
+    let samples = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            let mut search = Search::new(Some(start + timeout));
+            let result = search.solve(state);
+            let duration = start.elapsed();
+            nodes = search.nodes();
+            winnable = winnable.and(result);
+            Sample {
+                seed: 0,
+                outcome: match result {
+                    Some(true) => Outcome::Won,
+                    Some(false) => Outcome::Lost,
+                    None => Outcome::TimedOut,
+                },
+                duration,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    BenchReport {
+        runs,
+        winnable: if runs == 0 { None } else { winnable },
+        nodes,
+        duration: DurationSummary::new(&samples),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::toy_state;
+    use crate::tasks::Task;
+
+    #[test]
+    fn test_bench() {
+        let report = bench(&toy_state(Task::new(0, Y2)), 3, Duration::from_secs(5));
+        assert_eq!(report.runs, 3);
+        assert_eq!(report.winnable, Some(true));
+        assert!(report.nodes > 0);
+    }
+}
@@ -1,7 +1,7 @@
 use std::ops::{BitAnd, BitOr, Not};
 
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Card {
     B1 = 0,
     B2,
@@ -49,9 +49,23 @@ pub enum Card {
     R4,
 }
 
+impl Card {
+    pub const ALL: [Card; 40] = {
+        use Card::*;
+        [
+            B1, B2, B3, B4, B5, B6, B7, B8, B9, Y1, Y2, Y3, Y4, Y5, Y6, Y7, Y8, Y9, M1, M2, M3, M4,
+            M5, M6, M7, M8, M9, G1, G2, G3, G4, G5, G6, G7, G8, G9, R1, R2, R3, R4,
+        ]
+    };
+
+    pub fn from_index(index: u32) -> Option<Card> {
+        Self::ALL.get(index as usize).copied()
+    }
+}
+
 pub type RawCardSet = u64;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct CardSet(RawCardSet);
 
 impl CardSet {
@@ -121,6 +135,14 @@ impl CardSet {
         self.0.count_ones()
     }
 
+    pub fn cards(&self) -> Vec<Card> {
+        Card::ALL
+            .iter()
+            .filter(|c| self.contains(**c))
+            .copied()
+            .collect()
+    }
+
     const fn from_constant(cards: &[Card]) -> Self {
         match cards {
             [head, tail @ ..] => Self(Self::from_constant(tail).0 | Self::from_card(*head).0),
@@ -298,6 +320,23 @@ mod tests {
         assert_eq!(CardSet::from_cards(&[B1, G7, Y3, R2, M1]).num_set(), 5);
     }
 
+    #[test]
+    fn test_from_index() {
+        assert_eq!(Card::from_index(0), Some(B1));
+        assert_eq!(Card::from_index(9), Some(Y1));
+        assert_eq!(Card::from_index(39), Some(R4));
+        assert_eq!(Card::from_index(40), None);
+        for (i, card) in Card::ALL.iter().enumerate() {
+            assert_eq!(*card as usize, i);
+        }
+    }
+
+    #[test]
+    fn test_cards() {
+        assert_eq!(CardSet::EMPTY.cards(), vec![]);
+        assert_eq!(CardSet::from_cards(&[R2, B1, G7]).cards(), vec![B1, G7, R2]);
+    }
+
     #[test]
     fn test_bitwise_ops() {
         let x = CardSet::from_cards(&[B1, B3, B5]);
@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::play::{Hands, PlayGenerator};
+use crate::player::PlayerIndex;
+use crate::solver::GameState;
+use crate::tasks::TasksObjective;
+
+/// A proof that a game can't be won. Node 0 is the starting position. Each node has one entry per
+/// trick `PlayGenerator` produces in that position, in the same order: `None` if the trick fails a
+/// task, otherwise the ID of the node that refutes the position after the trick. Positions reached
+/// in more than one way share a node.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Certificate {
+    pub nodes: Vec<Vec<Option<usize>>>,
+}
+
+impl Certificate {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+}
+
+/// How many positions `certify` remembers by default, lost and won together. Unlike the solver's
+/// cache it can't forget any, as the lost ones make up the certificate, so a search that needs
+/// more gives up. With the hash tables' overhead each takes about 700 bytes, so this is up to
+/// around 700 MB.
+pub const DEFAULT_MAX_POSITIONS: usize = 1 << 20;
+
+#[derive(Debug, PartialEq)]
+pub enum CertifyError {
+    TimedOut,
+    /// More positions were needed than `max_positions` allows.
+    TooManyPositions,
+}
+
+/// Searches the game and records why every trick loses, remembering at most `max_positions`
+/// positions. Returns `Ok(None)` if the game can be won.
+pub fn certify(
+    state: &GameState,
+    timeout: Duration,
+    max_positions: usize,
+) -> Result<Option<Certificate>, CertifyError> {
+    let mut builder = Builder {
+        deadline: Instant::now() + timeout,
+        max_positions,
+        nodes: vec![],
+        ids: HashMap::new(),
+        won: HashSet::new(),
+    };
+    if state.is_won() {
+        return Ok(None);
+    }
+    let Some(root) = builder.refute(state)? else {
+        return Ok(None);
+    };
+    Ok(Some(builder.certificate(root)))
+}
+
+struct Builder {
+    deadline: Instant,
+    max_positions: usize,
+    nodes: Vec<Vec<Option<usize>>>,
+    ids: HashMap<GameState, usize>,
+    won: HashSet<GameState>,
+}
+
+impl Builder {
+    /// Returns the ID of the node refuting `state`, or `None` if it can be won.
+    fn refute(&mut self, state: &GameState) -> Result<Option<usize>, CertifyError> {
+        if let Some(id) = self.ids.get(state) {
+            return Ok(Some(*id));
+        }
+        if self.won.contains(state) {
+            return Ok(None);
+        }
+        if Instant::now() >= self.deadline {
+            return Err(CertifyError::TimedOut);
+        }
+        if self.ids.len() + self.won.len() >= self.max_positions {
+            return Err(CertifyError::TooManyPositions);
+        }
+
+        let mut entries = vec![];
+        for play in state.plays() {
+            let child = match state.after_trick(&play) {
+                None => {
+                    entries.push(None);
+                    continue;
+                }
+                Some(next) if next.is_won() => None,
+                Some(next) => self.refute(&next)?,
+            };
+            match child {
+                Some(id) => entries.push(Some(id)),
+                None => {
+                    self.won.insert(state.clone());
+                    return Ok(None);
+                }
+            }
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(entries);
+        self.ids.insert(state.clone(), id);
+        Ok(Some(id))
+    }
+
+    /// Keeps only the nodes reachable from `root`, numbered in the order they are reached.
+    fn certificate(&self, root: usize) -> Certificate {
+        let mut new_ids = HashMap::from([(root, 0)]);
+        let mut order = vec![root];
+        let mut i = 0;
+        while i < order.len() {
+            for child in self.nodes[order[i]].iter().flatten() {
+                if !new_ids.contains_key(child) {
+                    new_ids.insert(*child, order.len());
+                    order.push(*child);
+                }
+            }
+            i += 1;
+        }
+
+        Certificate {
+            nodes: order
+                .iter()
+                .map(|id| {
+                    self.nodes[*id]
+                        .iter()
+                        .map(|e| e.map(|child| new_ids[&child]))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A position as the checker sees it, rebuilt from the rules alone.
+#[derive(Clone, PartialEq)]
+struct Position {
+    hands: Hands,
+    tasks: TasksObjective,
+    leader: PlayerIndex,
+}
+
+/// Checks that `certificate` proves `state` can't be won. Only the trick generator and the task
+/// rules are used, none of the solver. Every node is checked once, against the first position
+/// that refers to it; as each trick removes cards, a node can't refute one of its own ancestors.
+pub fn check_certificate(state: &GameState, certificate: &Certificate) -> bool {
+    if certificate.nodes.is_empty() || state.tasks().is_complete() {
+        return false;
+    }
+
+    let mut positions: Vec<Option<Position>> = certificate.nodes.iter().map(|_| None).collect();
+    positions[0] = Some(Position {
+        hands: *state.hands(),
+        tasks: state.tasks().clone(),
+        leader: state.curr_leader(),
+    });
+    let mut pending = vec![0];
+
+    while let Some(id) = pending.pop() {
+        let position = positions[id].clone().unwrap();
+        let entries = &certificate.nodes[id];
+        let plays = PlayGenerator::new(&position.hands, position.leader).collect::<Vec<_>>();
+        if plays.len() != entries.len() {
+            return false;
+        }
+
+        for (play, entry) in plays.iter().zip(entries) {
+            let winning_card = play.get_trick_winner();
+            let Some(winner) = position
+                .hands
+                .iter()
+                .position(|h| h.overlaps_with(winning_card))
+            else {
+                return false;
+            };
+            let hands = position.hands.map(|h| h & !play.cards());
+            let tasks = position
+                .tasks
+                .check(play.cards(), winner as PlayerIndex, &hands);
+
+            let (id, child) = match (entry, tasks) {
+                (None, None) => continue,
+                (Some(id), Some(tasks)) if !tasks.is_complete() => (
+                    id,
+                    Position {
+                        hands,
+                        tasks,
+                        leader: winner as PlayerIndex,
+                    },
+                ),
+                _ => return false,
+            };
+            let Some(slot) = positions.get_mut(*id) else {
+                return false;
+            };
+            match slot {
+                // Already checked or waiting to be, so it has to be the same position.
+                Some(existing) if *existing == child => {}
+                Some(_) => return false,
+                None => {
+                    *slot = Some(child);
+                    pending.push(*id);
+                }
+            }
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::{endgame_state, toy_state};
+    use crate::tasks::Task;
+
+    #[test]
+    fn test_certify() {
+        let state = toy_state(Task::new(1, Y7));
+        let certificate = certify(&state, Duration::from_secs(5), DEFAULT_MAX_POSITIONS)
+            .unwrap()
+            .unwrap();
+        assert!(check_certificate(&state, &certificate));
+
+        let json = certificate.to_json();
+        assert_eq!(Certificate::from_json(&json), Some(certificate));
+    }
+
+    #[test]
+    fn test_certify_winnable() {
+        let state = toy_state(Task::new(0, Y2));
+        assert_eq!(
+            certify(&state, Duration::from_secs(5), DEFAULT_MAX_POSITIONS),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_bad_certificates() {
+        let lost = toy_state(Task::new(1, Y7));
+        let certificate = certify(&lost, Duration::from_secs(5), DEFAULT_MAX_POSITIONS)
+            .unwrap()
+            .unwrap();
+        // The same proof doesn't hold once the task can be done.
+        assert!(!check_certificate(
+            &toy_state(Task::new(0, Y2)),
+            &certificate
+        ));
+
+        // Every trick needs an entry.
+        let mut short = Certificate {
+            nodes: certificate.nodes.clone(),
+        };
+        short.nodes[0].pop();
+        assert!(!check_certificate(&lost, &short));
+
+        // A position can't be used to refute itself.
+        let mut cyclic = Certificate {
+            nodes: certificate.nodes.clone(),
+        };
+        for entry in cyclic.nodes[0].iter_mut() {
+            *entry = Some(0);
+        }
+        assert!(!check_certificate(&lost, &cyclic));
+
+        assert!(!check_certificate(&lost, &Certificate { nodes: vec![] }));
+        assert!(!check_certificate(
+            &lost,
+            &Certificate {
+                nodes: vec![vec![Some(7); certificate.nodes[0].len()]]
+            }
+        ));
+    }
+
+    #[test]
+    fn test_certify_endgame() {
+        // P1 holds R4, the top rocket left, so it wins whatever trick it's played in.
+        let state = endgame_state(0, 4, &[Task::new(1, R4)]);
+        assert!(state.hands()[0].contains(R4));
+        let certificate = certify(&state, Duration::from_secs(30), DEFAULT_MAX_POSITIONS)
+            .unwrap()
+            .unwrap();
+        assert!(certificate.nodes.len() > 1);
+        assert!(check_certificate(&state, &certificate));
+
+        assert_eq!(
+            certify(&state, Duration::from_secs(30), 1),
+            Err(CertifyError::TooManyPositions)
+        );
+    }
+}
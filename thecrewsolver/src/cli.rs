@@ -1,41 +1,156 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
 
 use crate::{
+    appstate::read_backend,
     card::{Card, CardSet},
-    play::Hands,
-    player::PlayerIndex,
+    consistency::{check, Issue},
+    hint::trick_start,
+    missions::get_mission,
+    notation::{is_notation, read_notation},
+    play::{Hands, Play},
+    player::{PlayerIndex, NUM_PLAYERS},
+    record::{Communication, GameRecord, Metadata, Position, Signal},
     solver::GameState,
-    tasks::{Task, TasksObjective},
+    tasks::{Task, TasksObjective, Token, MAX_TASKS, NUM_TRICKS},
 };
 
+/// The version of the JSON games and results are written in. Games from before versions were
+/// written are version 1, the fields the backend has always sent; version 2 added everything
+/// else, all of it optional, and `version` itself. See `schema` for the documents.
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct TaskDTO {
+    #[serde(default)]
+    pub(crate) task_type: String,
+    #[serde(default)]
+    pub(crate) order: u8,
+    pub(crate) card: String,
+    pub(crate) player_num: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) earliest: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) latest: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct State {
+    /// Missing in games from before versions were written.
+    #[serde(default = "first_version")]
+    pub(crate) version: u32,
+    pub(crate) hands: Vec<Vec<String>>,
+    pub(crate) tasks: Vec<TaskDTO>,
+    pub(crate) curr_leader: PlayerIndex,
+    #[serde(default)]
+    pub(crate) tricks_played: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) mission: Option<u8>,
+    /// Cards already played in the trick in progress, starting with the leader's. `hands` no
+    /// longer contain them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) current_trick: Vec<String>,
+    /// Tricks played from this position, each starting with the leader's card.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tricks: Vec<Vec<String>>,
+    /// Who was recorded as winning each of `tricks`, if anyone.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) winners: Vec<PlayerIndex>,
+}
+
 #[derive(Serialize, Deserialize)]
-struct TaskDTO {
-    task_type: String,
-    order: u8,
+struct CommunicationDTO {
+    trick: u8,
+    player_num: PlayerIndex,
     card: String,
-    player_num: u8,
+    signal: String,
 }
 
 #[derive(Serialize, Deserialize)]
-struct State {
-    hands: Vec<Vec<String>>,
-    tasks: Vec<TaskDTO>,
-    curr_leader: PlayerIndex,
+struct RecordDTO {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mission: Option<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    players: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+    deal: State,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    communications: Vec<CommunicationDTO>,
+    #[serde(default)]
+    tricks: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct VersionDTO {
+    #[serde(default = "first_version")]
+    version: u32,
+}
+
+/// How command line results are written.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("unknown output format `{}`", s)),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct RunOutput {
+    version: u32,
+    /// The caller's `id` for the game, in batch mode.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    id: Value,
     success: bool,
     result: bool,
     duration: u128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorDTO>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ErrorDTO {
+    pub(crate) code: String,
+    pub(crate) message: String,
 }
 
 impl RunOutput {
     pub fn new(success: bool, result: bool, duration: u128) -> Self {
         Self {
+            version: SCHEMA_VERSION,
+            id: Value::Null,
             success,
             result,
             duration,
+            error: None,
+        }
+    }
+
+    pub fn with_id(self, id: Value) -> Self {
+        Self { id, ..self }
+    }
+
+    /// An unsuccessful run that says why the input couldn't be read.
+    pub fn invalid(error: &ParseError) -> Self {
+        Self {
+            error: Some(ErrorDTO {
+                code: error.code().to_string(),
+                message: error.to_string(),
+            }),
+            ..Self::new(false, false, 0)
         }
     }
 
@@ -44,32 +159,638 @@ impl RunOutput {
     }
 }
 
-pub fn parse(input: &str) -> Option<GameState> {
-    let state: State = serde_json::from_str(input).ok()?;
+/// Writes a game in the JSON format that `parse` reads.
+pub fn format_state(state: &GameState, mission: Option<u8>) -> String {
+    serde_json::to_string(&to_state(state, mission)).unwrap()
+}
+
+/// Writes a game record in the JSON format `parse_record` reads, with the tasks of the deal typed.
+pub fn format_record(record: &GameRecord) -> String {
+    let record = RecordDTO {
+        mission: record.metadata.mission,
+        players: record.metadata.players.clone(),
+        date: record.metadata.date.clone(),
+        deal: to_state(&record.deal, None),
+        communications: record
+            .communications
+            .iter()
+            .map(|c| CommunicationDTO {
+                trick: c.trick,
+                player_num: c.player,
+                card: card_to_str(c.card),
+                signal: c.signal.name().to_string(),
+            })
+            .collect(),
+        tricks: record
+            .tricks
+            .iter()
+            .map(|t| t.iter().map(|c| card_to_str(*c)).collect())
+            .collect(),
+    };
+    serde_json::to_string(&record).unwrap()
+}
+
+pub(crate) fn to_state(state: &GameState, mission: Option<u8>) -> State {
+    let tasks = state.tasks();
+    State {
+        version: SCHEMA_VERSION,
+        hands: state.hands().iter().map(card_set_to_strs).collect(),
+        tasks: tasks
+            .to_tokens()
+            .iter()
+            .map(|(t, token)| task_to_dto(t, *token))
+            .collect(),
+        curr_leader: state.curr_leader(),
+        tricks_played: tasks.tricks_played(),
+        mission,
+        current_trick: vec![],
+        tricks: vec![],
+        winners: vec![],
+    }
+}
+
+/// Writes a deal in the same JSON format that `parse` reads.
+pub fn format_input(
+    hands: &[CardSet],
+    tasks: &[(Task, Option<Token>)],
+    curr_leader: PlayerIndex,
+    mission: Option<u8>,
+) -> String {
+    let state = State {
+        version: SCHEMA_VERSION,
+        hands: hands.iter().map(card_set_to_strs).collect(),
+        tasks: tasks
+            .iter()
+            .map(|(t, token)| task_to_dto(t, *token))
+            .collect(),
+        curr_leader,
+        tricks_played: 0,
+        mission,
+        current_trick: vec![],
+        tricks: vec![],
+        winners: vec![],
+    };
+    serde_json::to_string(&state).unwrap()
+}
+
+pub(crate) fn task_to_dto(task: &Task, token: Option<Token>) -> TaskDTO {
+    let (task_type, order) = match token {
+        Some(Token::Absolute(order)) => ("absolute", order),
+        Some(Token::Relative(order)) => ("relative", order),
+        Some(Token::Last) => ("last", 0),
+        None => ("anytime", 0),
+    };
+    TaskDTO {
+        task_type: task_type.to_string(),
+        order,
+        card: card_to_str(task.card()),
+        player_num: task.player(),
+        earliest: task.earliest(),
+        latest: task.latest(),
+    }
+}
+
+fn card_set_to_strs(cards: &CardSet) -> Vec<String> {
+    cards.cards().into_iter().map(card_to_str).collect()
+}
+
+/// Why an input couldn't be read.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// Not valid JSON, or a field is missing or has the wrong type.
+    Json {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    UnknownCard(String),
+    /// There must be one hand per player.
+    HandCount(usize),
+    UnknownTaskType(String),
+    /// A player number that isn't one of the players.
+    PlayerOutOfRange {
+        field: &'static str,
+        value: PlayerIndex,
+    },
+    UnknownMission(u8),
+    /// The mission has a rule the solver doesn't follow.
+    UnsupportedMission(u8),
+    /// The tasks don't fit the mission's tokens.
+    MissionMismatch(u8),
+    /// More than one task has the last trick token.
+    TooManyLastTasks,
+    /// More than `MAX_TASKS` tasks.
+    TooManyTasks(usize),
+    /// More tricks played than there are in a game.
+    TricksPlayed(u8),
+    /// A task's trick window reaching past the last trick.
+    TrickOutOfRange {
+        field: &'static str,
+        value: u8,
+    },
+    /// A trick window that no trick falls in.
+    TaskWindow {
+        earliest: u8,
+        latest: u8,
+    },
+    /// The field isn't accepted by this command, such as `tricks` when solving a position.
+    UnexpectedField(&'static str),
+    /// A card is listed twice in the same hand.
+    RepeatedCard {
+        card: String,
+        player: PlayerIndex,
+    },
+    /// More than one task is on the same card.
+    DuplicateTaskCard(String),
+    /// Two tasks of the same type have the same `order`.
+    DuplicateOrder {
+        task_type: String,
+        order: u8,
+    },
+    /// The game can't come from a real deal.
+    Deal(Issue),
+    /// Not valid compact notation; see `notation::format_notation`.
+    Notation(String),
+    /// A game record names some players but not all of them.
+    PlayerNames(usize),
+    UnknownSignal(String),
+    /// A schema version this solver doesn't know, usually a newer one.
+    UnsupportedVersion(u32),
+    /// A task of the backend's round that doesn't have its `card` or `player` yet.
+    IncompleteTask {
+        task: u32,
+        field: &'static str,
+    },
+    /// The backend's round can't be read; see `appstate::read_backend`.
+    AppState(String),
+    /// A task of the backend's round was failed in this trick, so there is no game left to
+    /// analyse. `parse_position` reads such a round as a lost position instead.
+    GameLost(u8),
+    /// `current_trick` couldn't have been played from the hands: it has too many cards, one of
+    /// them is still held, or a player didn't follow suit.
+    InvalidCurrentTrick,
+}
+
+impl ParseError {
+    /// A stable name for the kind of error, for programs to match on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::Json { .. } => "invalid_json",
+            ParseError::UnknownCard(_) => "unknown_card",
+            ParseError::HandCount(_) => "wrong_hand_count",
+            ParseError::UnknownTaskType(_) => "unknown_task_type",
+            ParseError::PlayerOutOfRange { .. } => "player_out_of_range",
+            ParseError::UnknownMission(_) => "unknown_mission",
+            ParseError::UnsupportedMission(_) => "unsupported_mission",
+            ParseError::MissionMismatch(_) => "mission_mismatch",
+            ParseError::TooManyLastTasks => "too_many_last_tasks",
+            ParseError::TooManyTasks(_) => "too_many_tasks",
+            ParseError::TricksPlayed(_) => "tricks_played_out_of_range",
+            ParseError::TrickOutOfRange { .. } => "trick_out_of_range",
+            ParseError::TaskWindow { .. } => "empty_task_window",
+            ParseError::UnexpectedField(_) => "unexpected_field",
+            ParseError::RepeatedCard { .. } => "repeated_card",
+            ParseError::DuplicateTaskCard(_) => "duplicate_task_card",
+            ParseError::DuplicateOrder { .. } => "duplicate_order",
+            ParseError::Deal(issue) => issue.code(),
+            ParseError::Notation(_) => "invalid_notation",
+            ParseError::PlayerNames(_) => "wrong_player_name_count",
+            ParseError::UnknownSignal(_) => "unknown_signal",
+            ParseError::UnsupportedVersion(_) => "unsupported_version",
+            ParseError::IncompleteTask { .. } => "incomplete_task",
+            ParseError::AppState(_) => "invalid_appstate",
+            ParseError::GameLost(_) => "game_lost",
+            ParseError::InvalidCurrentTrick => "invalid_current_trick",
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Json {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "invalid JSON at line {} column {}: {}",
+                line, column, message
+            ),
+            ParseError::UnknownCard(card) => write!(f, "unknown card `{}`", card),
+            ParseError::HandCount(count) => {
+                write!(f, "expected {} hands but found {}", NUM_PLAYERS, count)
+            }
+            ParseError::UnknownTaskType(task_type) => {
+                write!(f, "unknown task type `{}`", task_type)
+            }
+            ParseError::PlayerOutOfRange { field, value } => write!(
+                f,
+                "`{}` is {} but players are numbered 0 to {}",
+                field,
+                value,
+                NUM_PLAYERS - 1
+            ),
+            ParseError::UnknownMission(number) => write!(f, "unknown mission {}", number),
+            ParseError::UnsupportedMission(number) => {
+                write!(f, "mission {} has a rule the solver doesn't follow", number)
+            }
+            ParseError::MissionMismatch(number) => {
+                write!(f, "the tasks don't match mission {}", number)
+            }
+            ParseError::TooManyLastTasks => write!(f, "more than one task is marked last"),
+            ParseError::TooManyTasks(count) => write!(
+                f,
+                "{} tasks but a game can have at most {}",
+                count, MAX_TASKS
+            ),
+            ParseError::TricksPlayed(tricks) => write!(
+                f,
+                "`tricks_played` is {} but a game has {} tricks",
+                tricks, NUM_TRICKS
+            ),
+            ParseError::TrickOutOfRange { field, value } => write!(
+                f,
+                "`{}` is {} but a game has {} tricks",
+                field, value, NUM_TRICKS
+            ),
+            ParseError::TaskWindow { earliest, latest } => write!(
+                f,
+                "no trick is between trick {} and trick {}",
+                earliest, latest
+            ),
+            ParseError::UnexpectedField(field) => {
+                write!(f, "`{}` isn't accepted here", field)
+            }
+            ParseError::RepeatedCard { card, player } => {
+                write!(f, "`{}` is listed twice in hand {}", card, player)
+            }
+            ParseError::DuplicateTaskCard(card) => {
+                write!(f, "more than one task is on `{}`", card)
+            }
+            ParseError::DuplicateOrder { task_type, order } => {
+                write!(f, "more than one {} task has order {}", task_type, order)
+            }
+            ParseError::Deal(issue) => issue.fmt(f),
+            ParseError::Notation(message) => write!(f, "invalid notation: {}", message),
+            ParseError::PlayerNames(count) => write!(
+                f,
+                "expected {} player names but found {}",
+                NUM_PLAYERS, count
+            ),
+            ParseError::UnknownSignal(signal) => write!(f, "unknown signal `{}`", signal),
+            ParseError::UnsupportedVersion(version) => write!(
+                f,
+                "schema version {} isn't supported; versions 1 to {} are",
+                version, SCHEMA_VERSION
+            ),
+            ParseError::IncompleteTask { task, field } => {
+                write!(f, "task {} has no {} yet", task, field)
+            }
+            ParseError::AppState(message) => write!(f, "invalid appstate: {}", message),
+            ParseError::GameLost(trick) => write!(
+                f,
+                "a task was failed in trick {}, so the game can't be won",
+                trick
+            ),
+            ParseError::InvalidCurrentTrick => {
+                write!(
+                    f,
+                    "`current_trick` couldn't have been played from these hands"
+                )
+            }
+        }
+    }
+}
+
+/// Reads a game between tricks, as JSON or in the compact notation of `notation`. Use
+/// `parse_position` for games with a trick in progress. Games that couldn't come from a real deal
+/// are rejected with the first error `consistency::check` finds.
+pub fn parse(input: &str) -> Result<GameState, ParseError> {
+    let (state, issues) = parse_deal(input)?;
+    reject_inconsistent(&issues)?;
+    Ok(state)
+}
+
+/// Reads one line of batch input: a position as `parse_position` reads it, with any JSON value as
+/// its `id`. The id is `null` if there is none or the line can't be read at all.
+pub fn parse_batch_line(line: &str) -> (Value, Result<Position, ParseError>) {
+    if is_notation(line) {
+        return (Value::Null, parse_position(line));
+    }
+    let json = match Json::parse(line) {
+        Ok(json) => json,
+        Err(error) => return (Value::Null, Err(error)),
+    };
+    let id = json.value.get("id").cloned().unwrap_or(Value::Null);
+    (id, read_json_round(&json).and_then(to_position))
+}
+
+/// Like `parse`, but returns every consistency issue, errors included, instead of failing on
+/// them.
+pub fn parse_deal(input: &str) -> Result<(GameState, Vec<Issue>), ParseError> {
+    let (state, dto) = read(input)?;
+    reject_current_trick(&dto)?;
+    reject_tricks(&dto)?;
+    let issues = check(&state);
+    Ok((state, issues))
+}
+
+/// Reads a game along with the cards already played in the current trick. The deal is checked
+/// as it was at the start of the trick.
+pub fn parse_position(input: &str) -> Result<Position, ParseError> {
+    to_position(read_round(input)?)
+}
 
+fn to_position(
+    (state, dto, failed_trick): (GameState, State, Option<u8>),
+) -> Result<Position, ParseError> {
+    reject_tricks(&dto)?;
+    let current_trick = to_cards(&dto.current_trick)?;
+    // A lost round has had every card checked as it was replayed, and its tasks are left as they
+    // were when one was failed.
+    if failed_trick.is_none() {
+        let start = trick_start(&state, &current_trick).ok_or(ParseError::InvalidCurrentTrick)?;
+        reject_inconsistent(&check(&start))?;
+    }
+    Ok(Position {
+        state,
+        current_trick,
+        failed_trick,
+    })
+}
+
+/// Reads a game along with the tricks that were played from it.
+pub fn parse_replay(input: &str) -> Result<(GameState, Vec<Vec<Card>>), ParseError> {
+    let (state, tricks, _) = parse_transcript(input)?;
+    Ok((state, tricks))
+}
+
+/// A game, the tricks played from it and who was recorded as winning each of them.
+pub type Transcript = (GameState, Vec<Vec<Card>>, Vec<PlayerIndex>);
+
+/// Reads a game record: the deal, every trick played and optionally who won each of them.
+pub fn parse_transcript(input: &str) -> Result<Transcript, ParseError> {
+    let (state, dto) = read(input)?;
+    reject_current_trick(&dto)?;
+    reject_inconsistent(&check(&state))?;
+    let tricks = dto
+        .tricks
+        .iter()
+        .map(|t| to_cards(t))
+        .collect::<Result<Vec<_>, _>>()?;
+    for winner in &dto.winners {
+        check_player("winners", *winner)?;
+    }
+    Ok((state, tricks, dto.winners))
+}
+
+/// Reads a game record: metadata, the deal, communications and the tricks played. The deal must
+/// be one that could have been dealt, but the tricks are only checked once a position in them is
+/// asked for, with `GameRecord::position`.
+pub fn parse_record(input: &str) -> Result<GameRecord, ParseError> {
+    let mut record: RecordDTO = read_json(input)?;
+    record.deal = upgrade(record.deal)?;
+    let deal = to_game_state(&record.deal)?;
+    reject_current_trick(&record.deal)?;
+    reject_tricks(&record.deal)?;
+    reject_inconsistent(&check(&deal))?;
+    if !record.players.is_empty() && record.players.len() != NUM_PLAYERS {
+        return Err(ParseError::PlayerNames(record.players.len()));
+    }
+
+    let communications = record
+        .communications
+        .iter()
+        .map(|c| {
+            check_player("player_num", c.player_num)?;
+            Ok(Communication {
+                trick: c.trick,
+                player: c.player_num,
+                card: parse_card(&c.card)?,
+                signal: c
+                    .signal
+                    .parse::<Signal>()
+                    .map_err(|_| ParseError::UnknownSignal(c.signal.clone()))?,
+            })
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    let tricks = record
+        .tricks
+        .iter()
+        .map(|t| to_cards(t))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(GameRecord {
+        metadata: Metadata {
+            mission: record.mission,
+            players: record.players,
+            date: record.date,
+        },
+        deal,
+        communications,
+        tricks,
+    })
+}
+
+fn reject_current_trick(dto: &State) -> Result<(), ParseError> {
+    match dto.current_trick.is_empty() {
+        true => Ok(()),
+        false => Err(ParseError::UnexpectedField("current_trick")),
+    }
+}
+
+fn reject_tricks(dto: &State) -> Result<(), ParseError> {
+    match dto.tricks.is_empty() {
+        true => Ok(()),
+        false => Err(ParseError::UnexpectedField("tricks")),
+    }
+}
+
+fn reject_inconsistent(issues: &[Issue]) -> Result<(), ParseError> {
+    match issues.iter().find(|i| i.is_error()) {
+        Some(issue) => Err(ParseError::Deal(issue.clone())),
+        None => Ok(()),
+    }
+}
+
+fn read(input: &str) -> Result<(GameState, State), ParseError> {
+    match read_round(input)? {
+        (_, _, Some(trick)) => Err(ParseError::GameLost(trick)),
+        (state, dto, None) => Ok((state, dto)),
+    }
+}
+
+/// Like `read`, but also reads a backend round in which a task was already failed, with the trick
+/// it was failed in.
+fn read_round(input: &str) -> Result<(GameState, State, Option<u8>), ParseError> {
+    match is_notation(input) {
+        true => {
+            let state = read_notation(input)?;
+            Ok((to_game_state(&state)?, state, None))
+        }
+        false => read_json_round(&Json::parse(input)?),
+    }
+}
+
+fn read_json_round(json: &Json) -> Result<(GameState, State, Option<u8>), ParseError> {
+    let (state, failed_trick) = match read_backend(json) {
+        Some(round) => round?,
+        None => (read_state_json(json)?, None),
+    };
+    Ok((to_game_state(&state)?, state, failed_trick))
+}
+
+/// Reads a game in any schema version up to `SCHEMA_VERSION`. The version is read on its own
+/// first, so that a newer game is reported as such rather than by whichever field changed.
+fn read_state_json(json: &Json) -> Result<State, ParseError> {
+    let VersionDTO { version } = json.read()?;
+    check_version(version)?;
+    upgrade(json.read()?)
+}
+
+fn check_version(version: u32) -> Result<(), ParseError> {
+    match (1..=SCHEMA_VERSION).contains(&version) {
+        true => Ok(()),
+        false => Err(ParseError::UnsupportedVersion(version)),
+    }
+}
+
+/// Brings a game written in an older schema version up to `SCHEMA_VERSION`, which is what the
+/// rest of the parser expects. Version 2 only added optional fields, so version 1 games read as
+/// they are.
+fn upgrade(state: State) -> Result<State, ParseError> {
+    check_version(state.version)?;
+    Ok(State {
+        version: SCHEMA_VERSION,
+        ..state
+    })
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+pub(crate) fn to_game_state(state: &State) -> Result<GameState, ParseError> {
     let hands: Hands = state
         .hands
         .iter()
         .map(|h| to_card_set(&h.iter().map(|c| c.as_ref()).collect::<Vec<_>>()))
-        .collect::<Option<Vec<_>>>()?
+        .collect::<Result<Vec<_>, _>>()?
         .try_into()
-        .ok()?;
+        .map_err(|h: Vec<CardSet>| ParseError::HandCount(h.len()))?;
+    check_player("curr_leader", state.curr_leader)?;
+    if state.tasks.len() > MAX_TASKS {
+        return Err(ParseError::TooManyTasks(state.tasks.len()));
+    }
+    if state.tricks_played > NUM_TRICKS {
+        return Err(ParseError::TricksPlayed(state.tricks_played));
+    }
+    for (player, hand) in state.hands.iter().enumerate() {
+        if let Some(card) = repeated(hand) {
+            return Err(ParseError::RepeatedCard {
+                card: card.clone(),
+                player: player as PlayerIndex,
+            });
+        }
+    }
+    let task_cards = state
+        .tasks
+        .iter()
+        .map(|t| t.card.clone())
+        .collect::<Vec<_>>();
+    if let Some(card) = repeated(&task_cards) {
+        return Err(ParseError::DuplicateTaskCard(card.clone()));
+    }
+
+    let tasks = match state.mission {
+        Some(number) => mission_tasks(&state.tasks, number)?,
+        None => typed_tasks(&state.tasks)?,
+    }
+    .with_tricks_played(state.tricks_played);
+
+    Ok(GameState::new(hands, tasks, state.curr_leader))
+}
+
+fn read_json<T: DeserializeOwned>(input: &str) -> Result<T, ParseError> {
+    serde_json::from_str(input).map_err(|e| {
+        // serde_json ends its messages with the position, which `Display` already gives.
+        let message = e.to_string();
+        let position = format!(" at line {} column {}", e.line(), e.column());
+        ParseError::Json {
+            line: e.line(),
+            column: e.column(),
+            message: message
+                .strip_suffix(&position)
+                .unwrap_or(&message)
+                .to_string(),
+        }
+    })
+}
+
+/// A JSON document, parsed once and then read into as many DTOs as it takes.
+pub(crate) struct Json<'a> {
+    text: &'a str,
+    value: Value,
+}
+
+impl<'a> Json<'a> {
+    pub(crate) fn parse(text: &'a str) -> Result<Self, ParseError> {
+        Ok(Json {
+            text,
+            value: read_json(text)?,
+        })
+    }
 
-    let absolute_tasks = tasks_of_type(&state.tasks, "absolute")?;
-    let relative_tasks = tasks_of_type(&state.tasks, "relative")?;
-    let anytime_tasks = tasks_of_type(&state.tasks, "anytime")?;
-    let last_task = tasks_of_type(&state.tasks, "last")?.first().copied();
-    let tasks = TasksObjective::new(&absolute_tasks, &relative_tasks, &anytime_tasks, last_task);
+    pub(crate) fn read<T: DeserializeOwned>(&self) -> Result<T, ParseError> {
+        // Only the text knows where in it a field went wrong, so errors come from reading that.
+        T::deserialize(&self.value).or_else(|_| read_json(self.text))
+    }
+}
 
-    Some(GameState::new(hands, tasks, state.curr_leader))
+/// The first item that appears again later on.
+fn repeated<T: PartialEq>(items: &[T]) -> Option<&T> {
+    items
+        .iter()
+        .enumerate()
+        .find(|(i, item)| items[i + 1..].contains(item))
+        .map(|(_, item)| item)
 }
 
-fn to_card_set(cards: &[&str]) -> Option<CardSet> {
+fn check_player(field: &'static str, value: PlayerIndex) -> Result<(), ParseError> {
+    match (value as usize) < NUM_PLAYERS {
+        true => Ok(()),
+        false => Err(ParseError::PlayerOutOfRange { field, value }),
+    }
+}
+
+fn to_cards(cards: &[String]) -> Result<Vec<Card>, ParseError> {
+    cards.iter().map(|c| parse_card(c)).collect()
+}
+
+fn to_card_set(cards: &[&str]) -> Result<CardSet, ParseError> {
     let cards: Vec<Card> = cards
         .iter()
-        .map(|c| str_to_card(c))
-        .collect::<Option<Vec<_>>>()?;
-    Some(CardSet::from_cards(&cards))
+        .map(|c| parse_card(c))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(CardSet::from_cards(&cards))
+}
+
+pub(crate) fn parse_card(card_str: &str) -> Result<Card, ParseError> {
+    str_to_card(card_str).ok_or_else(|| ParseError::UnknownCard(card_str.to_string()))
+}
+
+/// The card each seat played in the trick, indexed by seat.
+pub(crate) fn cards_by_seat(state: &GameState, play: &Play) -> Vec<String> {
+    let mut cards = vec![String::new(); state.hands().len()];
+    for (seat, card) in state.cards_by_seat(play) {
+        cards[seat as usize] = card_to_str(card);
+    }
+    cards
+}
+
+pub(crate) fn card_to_str(card: Card) -> String {
+    format!("{:?}", card)
 }
 
 fn str_to_card(card_str: &str) -> Option<Card> {
@@ -91,17 +812,98 @@ fn str_to_card(card_str: &str) -> Option<Card> {
     }
 }
 
-fn tasks_of_type(tasks: &[TaskDTO], task_type: &str) -> Option<Vec<Task>> {
+fn typed_tasks(tasks: &[TaskDTO]) -> Result<TasksObjective, ParseError> {
+    if let Some(task) = tasks.iter().find(|t| task_token(t).is_none()) {
+        return Err(ParseError::UnknownTaskType(task.task_type.clone()));
+    }
+    let absolute_tasks = tasks_of_type(tasks, "absolute")?;
+    let relative_tasks = tasks_of_type(tasks, "relative")?;
+    let anytime_tasks = tasks_of_type(tasks, "anytime")?;
+    let last_tasks = tasks_of_type(tasks, "last")?;
+    if last_tasks.len() > 1 {
+        return Err(ParseError::TooManyLastTasks);
+    }
+    Ok(TasksObjective::new(
+        &absolute_tasks,
+        &relative_tasks,
+        &anytime_tasks,
+        last_tasks.first().copied(),
+    ))
+}
+
+/// Tasks for a logbook mission either all leave out their type, in which case the mission's
+/// tokens are handed out in the order the tasks are listed, or must match the mission exactly.
+fn mission_tasks(tasks: &[TaskDTO], number: u8) -> Result<TasksObjective, ParseError> {
+    let mission = get_mission(number).ok_or(ParseError::UnknownMission(number))?;
+    if !mission.is_supported() {
+        return Err(ParseError::UnsupportedMission(number));
+    }
+
+    if tasks.iter().all(|t| t.task_type.is_empty()) {
+        let tasks = tasks
+            .iter()
+            .map(dto_to_task)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(mission
+            .setup(&tasks)
+            .ok_or(ParseError::MissionMismatch(number))?
+            .tasks)
+    } else {
+        let tokens = tasks
+            .iter()
+            .map(|t| task_token(t).ok_or_else(|| ParseError::UnknownTaskType(t.task_type.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !mission.matches(&tokens) {
+            return Err(ParseError::MissionMismatch(number));
+        }
+        typed_tasks(tasks)
+    }
+}
+
+fn dto_to_task(task: &TaskDTO) -> Result<Task, ParseError> {
+    check_player("player_num", task.player_num)?;
+    let earliest = task.earliest.unwrap_or(1);
+    let latest = task.latest.unwrap_or(NUM_TRICKS);
+    if latest > NUM_TRICKS {
+        return Err(ParseError::TrickOutOfRange {
+            field: "latest",
+            value: latest,
+        });
+    }
+    if latest == 0 || earliest > latest {
+        return Err(ParseError::TaskWindow { earliest, latest });
+    }
+    Ok(Task::new(task.player_num, parse_card(&task.card)?).with_window(task.earliest, task.latest))
+}
+
+fn task_token(task: &TaskDTO) -> Option<Option<Token>> {
+    match task.task_type.as_ref() {
+        "absolute" => Some(Some(Token::Absolute(task.order))),
+        "relative" => Some(Some(Token::Relative(task.order))),
+        "last" => Some(Some(Token::Last)),
+        "anytime" => Some(None),
+        _ => None,
+    }
+}
+
+fn tasks_of_type(tasks: &[TaskDTO], task_type: &str) -> Result<Vec<Task>, ParseError> {
     let mut vec = tasks
         .iter()
         .filter(|t| t.task_type == task_type)
         .collect::<Vec<_>>();
 
-    vec.sort_by(|a, b| a.order.cmp(&b.order));
+    vec.sort_by_key(|t| t.order);
+    let ordered = task_type == "absolute" || task_type == "relative";
+    if let Some(pair) = vec.windows(2).find(|p| ordered && p[0].order == p[1].order) {
+        return Err(ParseError::DuplicateOrder {
+            task_type: task_type.to_string(),
+            order: pair[0].order,
+        });
+    }
 
     vec.iter()
-        .map(|t| Some(Task::new(t.player_num, str_to_card(&t.card)?)))
-        .collect::<Option<Vec<_>>>()
+        .map(|t| dto_to_task(t))
+        .collect::<Result<Vec<_>, _>>()
 }
 
 #[cfg(test)]
@@ -115,7 +917,7 @@ mod tests {
 
         assert_eq!(
             parse(json),
-            Some(GameState::new(
+            Ok(GameState::new(
                 [
                     CardSet::from_cards(&[B3]),
                     CardSet::from_cards(&[B9]),
@@ -128,6 +930,328 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_trick_window() {
+        let json = r#"{"hands":[["B3"], ["B9"], ["G3"], ["G2"]], "tasks":[{"task_type":"anytime","order":0,"card":"G2","player_num":1,"earliest":2,"latest":4}],"curr_leader":0,"tricks_played":1}"#;
+
+        assert_eq!(
+            parse(json),
+            Ok(GameState::new(
+                [
+                    CardSet::from_cards(&[B3]),
+                    CardSet::from_cards(&[B9]),
+                    CardSet::from_cards(&[G3]),
+                    CardSet::from_cards(&[G2])
+                ],
+                TasksObjective::new(
+                    &[],
+                    &[],
+                    &[Task::new(1, G2).with_window(Some(2), Some(4))],
+                    None
+                )
+                .with_tricks_played(1),
+                0
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_mission() {
+        let json = r#"{"hands":[["B3"], ["B9"], ["G3"], ["G2"]], "tasks":[{"task_type":"anytime","order":0,"card":"G2","player_num":1}],"curr_leader":0,"mission":1}"#;
+        assert!(parse(json).is_ok());
+
+        let json = r#"{"hands":[["B3"], ["B9"], ["G3"], ["G2"]], "tasks":[{"task_type":"absolute","order":1,"card":"G2","player_num":1}],"curr_leader":0,"mission":1}"#;
+        assert_eq!(parse(json), Err(ParseError::MissionMismatch(1)));
+
+        let json = r#"{"hands":[["B3"], ["B9"], ["G3"], ["G2"]], "tasks":[{"task_type":"anytime","order":0,"card":"G2","player_num":1}],"curr_leader":0,"mission":2}"#;
+        assert_eq!(parse(json), Err(ParseError::MissionMismatch(2)));
+
+        let json = r#"{"hands":[["B3"], ["B9"], ["G3"], ["G2"]], "tasks":[{"card":"G3","player_num":2},{"card":"G2","player_num":1}],"curr_leader":0,"mission":3}"#;
+        assert_eq!(
+            parse(json),
+            Ok(GameState::new(
+                [
+                    CardSet::from_cards(&[B3]),
+                    CardSet::from_cards(&[B9]),
+                    CardSet::from_cards(&[G3]),
+                    CardSet::from_cards(&[G2])
+                ],
+                TasksObjective::new(&[Task::new(2, G3), Task::new(1, G2)], &[], &[], None),
+                0
+            ))
+        );
+    }
+
+    #[test]
+    fn test_format_input() {
+        let hands = [
+            CardSet::from_cards(&[B3, R1]),
+            CardSet::from_cards(&[B9, Y1]),
+            CardSet::from_cards(&[G3, Y2]),
+            CardSet::from_cards(&[G2, Y3]),
+        ];
+        let tasks = [
+            (Task::new(1, G2), Some(Token::Absolute(1))),
+            (Task::new(2, B9), None),
+        ];
+        let json = format_input(&hands, &tasks, 2, None);
+
+        assert_eq!(
+            json,
+            r#"{"version":2,"hands":[["B3","R1"],["B9","Y1"],["Y2","G3"],["Y3","G2"]],"tasks":[{"task_type":"absolute","order":1,"card":"G2","player_num":1},{"task_type":"anytime","order":0,"card":"B9","player_num":2}],"curr_leader":2,"tricks_played":0}"#
+        );
+        assert_eq!(
+            parse(&json),
+            Ok(GameState::new(
+                hands,
+                TasksObjective::new(&[Task::new(1, G2)], &[], &[Task::new(2, B9)], None),
+                2
+            ))
+        );
+    }
+
+    #[test]
+    fn test_versions() {
+        let unversioned = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"absolute","order":1,"card":"G2","player_num":1}],"curr_leader":0}"#;
+        let state = parse(unversioned).unwrap();
+        assert!(format_state(&state, None).starts_with(r#"{"version":2,"#));
+        let versioned = unversioned.replacen('{', r#"{"version":1,"#, 1);
+        assert_eq!(parse(&versioned), Ok(state));
+
+        let newer = r#"{"version":3,"hands":{"P1":["B3"]},"curr_leader":"P1"}"#;
+        let error = parse(newer).unwrap_err();
+        assert_eq!(error.code(), "unsupported_version");
+        assert_eq!(
+            error.to_string(),
+            "schema version 3 isn't supported; versions 1 to 2 are"
+        );
+        let record = format!(
+            r#"{{"deal":{}}}"#,
+            unversioned.replacen('{', r#"{"version":0,"#, 1)
+        );
+        assert_eq!(
+            parse_record(&record),
+            Err(ParseError::UnsupportedVersion(0))
+        );
+    }
+
+    #[test]
+    fn test_format_state() {
+        let json = r#"{"version":2,"hands":[["B3","R1"],["B9","Y1"],["Y2","G3"],["Y3","G2"]],"tasks":[{"task_type":"absolute","order":1,"card":"G2","player_num":1},{"task_type":"last","order":0,"card":"B9","player_num":2,"latest":4}],"curr_leader":2,"tricks_played":3}"#;
+        let state = parse(json).unwrap();
+        assert_eq!(format_state(&state, None), json);
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],[]],"tasks":[],"curr_leader":3,"current_trick":["G2"]}"#;
+        let position = parse_position(json).unwrap();
+        assert_eq!(position.current_trick, vec![G2]);
+        assert_eq!(position.state.curr_leader(), 3);
+        assert_eq!(position.failed_trick, None);
+        assert_eq!(
+            parse(json),
+            Err(ParseError::UnexpectedField("current_trick"))
+        );
+
+        // B3 is still held, and P3 still holds B5 so can't play G3 on a blue lead.
+        for json in [
+            r#"{"hands":[["B3"],["B9"],["G3"],[]],"tasks":[],"curr_leader":3,"current_trick":["B3"]}"#,
+            r#"{"hands":[["B3"],[],["B5"],["G2"]],"tasks":[],"curr_leader":1,"current_trick":["B9","G3"]}"#,
+        ] {
+            assert_eq!(parse_position(json), Err(ParseError::InvalidCurrentTrick));
+        }
+    }
+
+    #[test]
+    fn test_parse_replay() {
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0,"tricks":[["B3","B9","G3","G2"]]}"#;
+        let (_, tricks) = parse_replay(json).unwrap();
+        assert_eq!(tricks, vec![vec![B3, B9, G3, G2]]);
+        assert_eq!(parse(json), Err(ParseError::UnexpectedField("tricks")));
+        assert_eq!(
+            parse_position(json),
+            Err(ParseError::UnexpectedField("tricks"))
+        );
+    }
+
+    #[test]
+    fn test_parse_transcript() {
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0,"tricks":[["B3","B9","G3","G2"]],"winners":[1]}"#;
+        let (_, tricks, winners) = parse_transcript(json).unwrap();
+        assert_eq!(tricks, vec![vec![B3, B9, G3, G2]]);
+        assert_eq!(winners, vec![1]);
+
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0,"tricks":[],"winners":[4]}"#;
+        assert_eq!(
+            parse_transcript(json),
+            Err(ParseError::PlayerOutOfRange {
+                field: "winners",
+                value: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_record() {
+        let json = r#"{"mission":3,"players":["Ann","Bo","Cy","Di"],"date":"2024-05-17","deal":{"version":2,"hands":[["B3","R1"],["B9","Y1"],["Y2","G3"],["Y3","G2"]],"tasks":[{"task_type":"absolute","order":1,"card":"G3","player_num":1}],"curr_leader":0,"tricks_played":8},"communications":[{"trick":1,"player_num":2,"card":"G3","signal":"only"}],"tricks":[["B3","B9","Y2","Y3"]]}"#;
+        let record = parse_record(json).unwrap();
+        assert_eq!(record.metadata.mission, Some(3));
+        assert_eq!(record.metadata.players[3], "Di");
+        assert_eq!(
+            record.communications,
+            vec![Communication {
+                trick: 1,
+                player: 2,
+                card: G3,
+                signal: Signal::Only
+            }]
+        );
+        assert_eq!(record.tricks, vec![vec![B3, B9, Y2, Y3]]);
+        assert_eq!(format_record(&record), json);
+
+        let json = r#"{"players":["Ann"],"deal":{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0}}"#;
+        assert_eq!(parse_record(json), Err(ParseError::PlayerNames(1)));
+        let json = r#"{"deal":{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0},"communications":[{"trick":1,"player_num":0,"card":"B3","signal":"top"}]}"#;
+        assert_eq!(
+            parse_record(json).unwrap_err().to_string(),
+            "unknown signal `top`"
+        );
+        let json = r#"{"deal":{"hands":[["B3"],["B3"],["G3"],["G2"]],"tasks":[],"curr_leader":0}}"#;
+        assert_eq!(parse_record(json).unwrap_err().code(), "card_in_two_hands");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let code = |json: &str| parse(json).unwrap_err().code();
+
+        let error = parse("{\n  \"hands\": [,]}").unwrap_err();
+        assert!(matches!(
+            error,
+            ParseError::Json {
+                line: 2,
+                column: 13,
+                ..
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "invalid JSON at line 2 column 13: expected value"
+        );
+        assert_eq!(code(r#"{"hands":[]}"#), "invalid_json");
+
+        let json = r#"{"hands":[["B0"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0}"#;
+        assert_eq!(parse(json), Err(ParseError::UnknownCard("B0".to_string())));
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"anytime","card":"R5","player_num":0}],"curr_leader":0}"#;
+        assert_eq!(code(json), "unknown_card");
+
+        let json = r#"{"hands":[["B3"],["B9"],["G3"]],"tasks":[],"curr_leader":0}"#;
+        assert_eq!(parse(json), Err(ParseError::HandCount(3)));
+
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"first","card":"G2","player_num":0}],"curr_leader":0}"#;
+        assert_eq!(
+            parse(json),
+            Err(ParseError::UnknownTaskType("first".to_string()))
+        );
+
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"anytime","card":"G2","player_num":4}],"curr_leader":0}"#;
+        assert_eq!(
+            parse(json).unwrap_err().to_string(),
+            "`player_num` is 4 but players are numbered 0 to 3"
+        );
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":7}"#;
+        assert_eq!(code(json), "player_out_of_range");
+
+        let json =
+            r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0,"mission":99}"#;
+        assert_eq!(parse(json), Err(ParseError::UnknownMission(99)));
+        let json =
+            r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0,"mission":13}"#;
+        assert_eq!(parse(json), Err(ParseError::UnsupportedMission(13)));
+
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"last","card":"G2","player_num":0},{"task_type":"last","card":"G3","player_num":0}],"curr_leader":0}"#;
+        assert_eq!(code(json), "too_many_last_tasks");
+
+        let task = r#"{"task_type":"anytime","card":"G2","player_num":0}"#;
+        let json = format!(
+            r#"{{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{}],"curr_leader":0}}"#,
+            vec![task; MAX_TASKS + 1].join(",")
+        );
+        assert_eq!(parse(&json), Err(ParseError::TooManyTasks(MAX_TASKS + 1)));
+
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0,"tricks_played":255}"#;
+        assert_eq!(parse(json), Err(ParseError::TricksPlayed(255)));
+
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"anytime","card":"G2","player_num":0,"earliest":4,"latest":2}],"curr_leader":0}"#;
+        assert_eq!(
+            parse(json),
+            Err(ParseError::TaskWindow {
+                earliest: 4,
+                latest: 2
+            })
+        );
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"anytime","card":"G2","player_num":0,"latest":0}],"curr_leader":0}"#;
+        assert_eq!(code(json), "empty_task_window");
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"anytime","card":"G2","player_num":0,"latest":11}],"curr_leader":0}"#;
+        assert_eq!(
+            parse(json).unwrap_err().to_string(),
+            "`latest` is 11 but a game has 10 tricks"
+        );
+
+        let json = r#"{"hands":[["B3","B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0}"#;
+        assert_eq!(
+            parse(json).unwrap_err().to_string(),
+            "`B3` is listed twice in hand 0"
+        );
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"anytime","card":"G2","player_num":0},{"task_type":"absolute","order":1,"card":"G2","player_num":1}],"curr_leader":0}"#;
+        assert_eq!(
+            parse(json),
+            Err(ParseError::DuplicateTaskCard("G2".to_string()))
+        );
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"relative","order":2,"card":"G2","player_num":0},{"task_type":"relative","order":2,"card":"G3","player_num":1}],"curr_leader":0}"#;
+        assert_eq!(
+            parse(json).unwrap_err().to_string(),
+            "more than one relative task has order 2"
+        );
+
+        // Checked like `consistency::check`, reporting the first error.
+        let json = r#"{"hands":[["B3"],["B3"],["G3"],["G2"]],"tasks":[{"task_type":"anytime","card":"G5","player_num":0}],"curr_leader":0}"#;
+        let error = parse(json).unwrap_err();
+        assert_eq!(error.code(), "card_in_two_hands");
+        assert_eq!(error.to_string(), "B3 is held by P1 and P2");
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[{"task_type":"anytime","card":"G5","player_num":0}],"curr_leader":0,"tricks_played":9}"#;
+        assert_eq!(code(json), "task_card_not_held");
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"current_trick":["B1"],"tasks":[],"curr_leader":0,"tricks_played":9}"#;
+        assert_eq!(parse_position(json).unwrap_err().code(), "unequal_hands");
+
+        let json = r#"{"hands":[["B3"],["B9"],["G3"],["G2"]],"tasks":[],"curr_leader":0}"#;
+        let (_, issues) = parse_deal(json).unwrap();
+        assert_eq!(
+            issues.iter().map(|i| i.code()).collect::<Vec<_>>(),
+            vec!["tricks_played_mismatch", "no_tasks"]
+        );
+    }
+
+    #[test]
+    fn test_run_output() {
+        let output = RunOutput::invalid(&ParseError::HandCount(3)).to_json();
+        assert_eq!(
+            output,
+            r#"{"version":2,"success":false,"result":false,"duration":0,"error":{"code":"wrong_hand_count","message":"expected 4 hands but found 3"}}"#
+        );
+        assert_eq!(
+            RunOutput::new(true, true, 5).to_json(),
+            r#"{"version":2,"success":true,"result":true,"duration":5}"#
+        );
+    }
+
+    #[test]
+    fn test_card_to_str() {
+        for card in Card::ALL {
+            assert_eq!(str_to_card(&card_to_str(card)), Some(card));
+        }
+    }
+
     #[test]
     fn test_str_to_card() {
         assert_eq!(str_to_card("B1"), Some(B1));
@@ -161,9 +1285,12 @@ mod tests {
     fn test_to_card_set() {
         assert_eq!(
             to_card_set(&["B6", "M9"]),
-            Some(CardSet::from_cards(&[B6, M9]))
+            Ok(CardSet::from_cards(&[B6, M9]))
+        );
+        assert_eq!(
+            to_card_set(&["B0", "M9"]),
+            Err(ParseError::UnknownCard("B0".to_string()))
         );
-        assert_eq!(to_card_set(&["B0", "M9"]), None);
     }
 
     #[test]
@@ -174,10 +1301,12 @@ mod tests {
                 order,
                 card: card.to_string(),
                 player_num,
+                earliest: None,
+                latest: None,
             }
         }
 
-        assert_eq!(tasks_of_type(&[], "absolute"), Some(vec![]));
+        assert_eq!(tasks_of_type(&[], "absolute"), Ok(vec![]));
         assert_eq!(
             tasks_of_type(
                 &[
@@ -187,14 +1316,14 @@ mod tests {
                 ],
                 "absolute"
             ),
-            Some(vec![Task::new(1, B1), Task::new(3, M3)])
+            Ok(vec![Task::new(1, B1), Task::new(3, M3)])
         );
         assert_eq!(
             tasks_of_type(
                 &[t("absolute", 2, "M3", 3), t("absolute", 1, "B1", 1)],
                 "absolute"
             ),
-            Some(vec![Task::new(1, B1), Task::new(3, M3)])
+            Ok(vec![Task::new(1, B1), Task::new(3, M3)])
         );
     }
 }
@@ -0,0 +1,255 @@
+use std::fmt;
+
+use crate::card::{Card, CardSet};
+use crate::player::PlayerIndex;
+use crate::solver::GameState;
+use crate::tasks::{Task, NUM_TRICKS};
+
+/// Something wrong with a game before any of it is solved.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Issue {
+    /// The card is in more than one hand.
+    CardInTwoHands {
+        card: Card,
+        players: Vec<PlayerIndex>,
+    },
+    /// Between tricks every player holds the same number of cards.
+    UnequalHands { sizes: Vec<u32> },
+    /// The leader has no card to lead while someone else still has cards.
+    LeaderHasNoCards { leader: PlayerIndex },
+    /// No one holds the task's card, so it can never be won.
+    TaskCardNotHeld { task: Task },
+    /// More than one task is on the same card.
+    TwoTasksOnCard { card: Card },
+    /// The task's trick window is over, or starts after the last trick.
+    TaskWindowMissed { task: Task },
+    /// Warning: the hands don't have as many cards as `tricks_played` leaves.
+    TricksPlayedMismatch { tricks_played: u8, hand_size: u32 },
+    /// Warning: there is nothing to win, so any play wins.
+    NoTasks,
+}
+
+impl Issue {
+    /// Errors make the game impossible to play as given; the rest are warnings.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Issue::TricksPlayedMismatch { .. } | Issue::NoTasks)
+    }
+
+    /// A stable name for the kind of issue, for programs to match on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Issue::CardInTwoHands { .. } => "card_in_two_hands",
+            Issue::UnequalHands { .. } => "unequal_hands",
+            Issue::LeaderHasNoCards { .. } => "leader_has_no_cards",
+            Issue::TaskCardNotHeld { .. } => "task_card_not_held",
+            Issue::TwoTasksOnCard { .. } => "two_tasks_on_card",
+            Issue::TaskWindowMissed { .. } => "task_window_missed",
+            Issue::TricksPlayedMismatch { .. } => "tricks_played_mismatch",
+            Issue::NoTasks => "no_tasks",
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::CardInTwoHands { card, players } => {
+                let players = players
+                    .iter()
+                    .map(|p| format!("P{}", p + 1))
+                    .collect::<Vec<_>>();
+                write!(f, "{:?} is held by {}", card, players.join(" and "))
+            }
+            Issue::UnequalHands { sizes } => {
+                let sizes = sizes.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+                write!(f, "hands have {} cards", sizes.join(", "))
+            }
+            Issue::LeaderHasNoCards { leader } => {
+                write!(f, "P{} leads but has no cards", leader + 1)
+            }
+            Issue::TaskCardNotHeld { task } => write!(
+                f,
+                "P{} has a task on {:?} but no one holds it",
+                task.player() + 1,
+                task.card()
+            ),
+            Issue::TwoTasksOnCard { card } => write!(f, "more than one task is on {:?}", card),
+            Issue::TaskWindowMissed { task } => write!(
+                f,
+                "P{}'s task on {:?} can't be done in any trick that is left",
+                task.player() + 1,
+                task.card()
+            ),
+            Issue::TricksPlayedMismatch {
+                tricks_played,
+                hand_size,
+            } => write!(
+                f,
+                "hands have {} cards each, but {} tricks have been played",
+                hand_size, tricks_played
+            ),
+            Issue::NoTasks => write!(f, "there are no tasks, so the game is already won"),
+        }
+    }
+}
+
+/// Checks that a game between tricks could come from a real deal: every card is held once, the
+/// hands are even, the leader can lead and every task can still be done. Errors come first.
+pub fn check(state: &GameState) -> Vec<Issue> {
+    let hands = state.hands();
+    let tasks = state.tasks().to_tokens();
+    let mut issues = vec![];
+
+    let mut held = CardSet::EMPTY;
+    let mut doubled = CardSet::EMPTY;
+    for hand in hands {
+        doubled = doubled | (*hand & held);
+        held = held | *hand;
+    }
+    for card in doubled.cards() {
+        let players = (0..hands.len())
+            .filter(|p| hands[*p].contains(card))
+            .map(|p| p as PlayerIndex)
+            .collect();
+        issues.push(Issue::CardInTwoHands { card, players });
+    }
+
+    let sizes = hands.iter().map(|h| h.num_set()).collect::<Vec<_>>();
+    let even = sizes.iter().all(|s| *s == sizes[0]);
+    if !even {
+        issues.push(Issue::UnequalHands {
+            sizes: sizes.clone(),
+        });
+    }
+    let leader = state.curr_leader();
+    if sizes[leader as usize] == 0 && sizes.iter().any(|s| *s > 0) {
+        issues.push(Issue::LeaderHasNoCards { leader });
+    }
+
+    let tricks_played = state.tasks().tricks_played();
+    let last_trick = tricks_played as u32 + sizes.iter().max().unwrap_or(&0);
+    let mut task_cards = CardSet::EMPTY;
+    for (task, _) in &tasks {
+        let card = CardSet::from_card(task.card());
+        if !held.overlaps_with(card) {
+            issues.push(Issue::TaskCardNotHeld { task: *task });
+        }
+        if task_cards.overlaps_with(card) {
+            issues.push(Issue::TwoTasksOnCard { card: task.card() });
+        }
+        task_cards = task_cards | card;
+
+        let over = task.latest().is_some_and(|latest| latest <= tricks_played);
+        let after_end = task.earliest().is_some_and(|e| e as u32 > last_trick);
+        if over || after_end {
+            issues.push(Issue::TaskWindowMissed { task: *task });
+        }
+    }
+
+    let hand_size = sizes[0];
+    if even && hand_size + tricks_played as u32 != NUM_TRICKS as u32 {
+        issues.push(Issue::TricksPlayedMismatch {
+            tricks_played,
+            hand_size,
+        });
+    }
+    if tasks.is_empty() {
+        issues.push(Issue::NoTasks);
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::{dealt_state, toy_state};
+    use crate::tasks::TasksObjective;
+
+    /// The end of a game, with 8 tricks played.
+    fn endgame(hands: [&[Card]; 4], tasks: &[Task]) -> GameState {
+        GameState::new(
+            hands.map(CardSet::from_cards),
+            TasksObjective::new(&[], &[], tasks, None).with_tricks_played(8),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_consistent_deal() {
+        assert_eq!(check(&dealt_state(0, 3)), vec![]);
+
+        let state = toy_state(Task::new(0, Y2));
+        let issues = check(&state);
+        assert_eq!(
+            issues,
+            vec![Issue::TricksPlayedMismatch {
+                tricks_played: 0,
+                hand_size: 2
+            }]
+        );
+        assert!(!issues[0].is_error());
+    }
+
+    #[test]
+    fn test_cards() {
+        let state = endgame(
+            [&[B1, B9], &[B1, Y2], &[Y3, B1], &[B4]],
+            &[Task::new(0, Y2)],
+        );
+        let issues = check(&state);
+        assert_eq!(
+            issues,
+            vec![
+                Issue::CardInTwoHands {
+                    card: B1,
+                    players: vec![0, 1, 2]
+                },
+                Issue::UnequalHands {
+                    sizes: vec![2, 2, 2, 1]
+                },
+            ]
+        );
+        assert_eq!(issues[0].to_string(), "B1 is held by P1 and P2 and P3");
+        assert_eq!(issues[1].to_string(), "hands have 2, 2, 2, 1 cards");
+    }
+
+    #[test]
+    fn test_leader() {
+        let state = GameState::new(
+            [&[][..], &[B2], &[B3], &[B4]].map(CardSet::from_cards),
+            TasksObjective::new(&[], &[], &[Task::new(1, B4)], None).with_tricks_played(9),
+            0,
+        );
+        assert!(check(&state).contains(&Issue::LeaderHasNoCards { leader: 0 }));
+    }
+
+    #[test]
+    fn test_tasks() {
+        let not_held = Task::new(1, G5);
+        let missed = Task::new(2, Y3).with_window(None, Some(8));
+        let hands: [&[Card]; 4] = [&[B1, B9], &[B2, Y2], &[Y3, Y7], &[B4, Y4]];
+        let tasks = TasksObjective::new(
+            &[Task::new(0, Y2)],
+            &[],
+            &[Task::new(3, Y2), not_held, missed],
+            None,
+        );
+        let state = GameState::new(
+            hands.map(CardSet::from_cards),
+            tasks.with_tricks_played(8),
+            0,
+        );
+        assert_eq!(
+            check(&state),
+            vec![
+                Issue::TwoTasksOnCard { card: Y2 },
+                Issue::TaskCardNotHeld { task: not_held },
+                Issue::TaskWindowMissed { task: missed },
+            ]
+        );
+        assert!(check(&state).iter().all(Issue::is_error));
+
+        assert_eq!(check(&endgame(hands, &[])), vec![Issue::NoTasks]);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::card::{suit, Card, CardSet};
+use crate::missions::Mission;
+use crate::play::Hands;
+use crate::player::{PlayerIndex, NUM_PLAYERS};
+use crate::rng::Rng;
+use crate::solver::GameState;
+use crate::tasks::{Task, TasksObjective, Token};
+
+pub const MIN_PLAYERS: usize = 3;
+pub const MAX_PLAYERS: usize = 5;
+
+const COMMANDER_CARD: Card = Card::R4;
+
+#[derive(Debug, PartialEq)]
+pub struct Deal {
+    hands: Vec<CardSet>,
+    commander: PlayerIndex,
+}
+
+impl Deal {
+    pub fn hands(&self) -> &[CardSet] {
+        &self.hands
+    }
+
+    /// The player holding the 4 rocket, who leads the first trick.
+    pub fn commander(&self) -> PlayerIndex {
+        self.commander
+    }
+
+    /// The hands in the fixed-size form the solver works with, if the player count matches.
+    pub fn to_hands(&self) -> Option<Hands> {
+        self.hands.clone().try_into().ok()
+    }
+}
+
+/// How many tasks to draw for a game and which tokens go with them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskTemplate {
+    pub num_tasks: usize,
+    pub tokens: Vec<Token>,
+}
+
+impl TaskTemplate {
+    pub fn new(num_tasks: usize, tokens: &[Token]) -> TaskTemplate {
+        TaskTemplate {
+            num_tasks,
+            tokens: tokens.to_vec(),
+        }
+    }
+
+    pub fn from_mission(mission: &Mission) -> TaskTemplate {
+        Self::new(mission.num_tasks() as usize, mission.tokens())
+    }
+
+    /// Hands out the tokens to the first tasks in draw order.
+    pub fn assign_tokens(&self, tasks: &[Task]) -> Vec<(Task, Option<Token>)> {
+        tasks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (*t, self.tokens.get(i).copied()))
+            .collect()
+    }
+}
+
+/// Deals cards and draws tasks from a seed, the same way the physical decks are used.
+pub struct DealGenerator {
+    rng: Rng,
+}
+
+impl DealGenerator {
+    pub fn new(seed: u64) -> DealGenerator {
+        DealGenerator {
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Shuffles all 40 cards and deals them out one at a time. With 3 players the first player
+    /// ends up with one card more than the others.
+    pub fn deal(&mut self, num_players: usize) -> Option<Deal> {
+        (MIN_PLAYERS..=MAX_PLAYERS)
+            .contains(&num_players)
+            .then_some(())?;
+
+        let mut deck = Card::ALL;
+        self.rng.shuffle(&mut deck);
+
+        let mut hands = vec![CardSet::EMPTY; num_players];
+        for (i, card) in deck.iter().enumerate() {
+            let hand = &mut hands[i % num_players];
+            *hand = hand.add(CardSet::from_card(*card));
+        }
+
+        let commander = hands.iter().position(|h| h.contains(COMMANDER_CARD))? as PlayerIndex;
+        Some(Deal { hands, commander })
+    }
+
+    /// Draws task cards from the task deck (every card except the rockets) and hands them out
+    /// in turn, starting with the commander.
+    pub fn draw_tasks(&mut self, deal: &Deal, num_tasks: usize) -> Vec<Task> {
+        let mut deck = CardSet::from_cards(&Card::ALL) & !suit::ROCKETS;
+        let num_players = deal.hands.len();
+
+        (0..num_tasks)
+            .map_while(|i| {
+                let cards = deck.cards();
+                let card = *cards.get(self.rng.below(cards.len().max(1)))?;
+                deck = deck & !CardSet::from_card(card);
+                let player = (deal.commander as usize + i) % num_players;
+                Some(Task::new(player as PlayerIndex, card))
+            })
+            .collect()
+    }
+
+    /// Deals a 4 player game and draws tasks for it, led by the commander.
+    pub fn game(&mut self, template: &TaskTemplate) -> Option<GameState> {
+        let deal = self.deal(NUM_PLAYERS)?;
+        let tasks = self.draw_tasks(&deal, template.num_tasks);
+        Some(GameState::new(
+            deal.to_hands()?,
+            TasksObjective::from_tokens(&template.assign_tokens(&tasks))?,
+            deal.commander(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deal_is_reproducible() {
+        assert_eq!(DealGenerator::new(5).deal(4), DealGenerator::new(5).deal(4));
+        assert_ne!(DealGenerator::new(5).deal(4), DealGenerator::new(6).deal(4));
+    }
+
+    #[test]
+    fn test_deal_uses_every_card_once() {
+        for num_players in MIN_PLAYERS..=MAX_PLAYERS {
+            let deal = DealGenerator::new(1).deal(num_players).unwrap();
+            assert_eq!(deal.hands().len(), num_players);
+
+            let all = deal.hands().iter().fold(CardSet::EMPTY, |a, h| {
+                assert!(!a.overlaps_with(*h));
+                a | *h
+            });
+            assert_eq!(all, CardSet::from_cards(&Card::ALL));
+
+            let sizes = deal.hands().iter().map(|h| h.num_set()).collect::<Vec<_>>();
+            assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1);
+        }
+    }
+
+    #[test]
+    fn test_deal_player_count() {
+        assert_eq!(DealGenerator::new(1).deal(2), None);
+        assert_eq!(DealGenerator::new(1).deal(6), None);
+        assert!(DealGenerator::new(1).deal(3).unwrap().to_hands().is_none());
+        assert!(DealGenerator::new(1).deal(4).unwrap().to_hands().is_some());
+    }
+
+    #[test]
+    fn test_commander_holds_r4() {
+        let deal = DealGenerator::new(9).deal(4).unwrap();
+        assert!(deal.hands()[deal.commander() as usize].contains(Card::R4));
+    }
+
+    #[test]
+    fn test_draw_tasks() {
+        let mut generator = DealGenerator::new(2);
+        let deal = generator.deal(4).unwrap();
+        let tasks = generator.draw_tasks(&deal, 6);
+
+        assert_eq!(tasks.len(), 6);
+        assert_eq!(tasks[0].player(), deal.commander());
+        assert_eq!(tasks[1].player(), (deal.commander() + 1) % 4);
+        for (i, task) in tasks.iter().enumerate() {
+            assert!(!suit::ROCKETS.contains(task.card()));
+            assert!(tasks[i + 1..].iter().all(|t| t.card() != task.card()));
+        }
+    }
+
+    #[test]
+    fn test_assign_tokens() {
+        let template = TaskTemplate::new(3, &[Token::Absolute(1), Token::Last]);
+        let tasks = [
+            Task::new(0, Card::B1),
+            Task::new(1, Card::B2),
+            Task::new(2, Card::B3),
+        ];
+        assert_eq!(
+            template.assign_tokens(&tasks),
+            vec![
+                (tasks[0], Some(Token::Absolute(1))),
+                (tasks[1], Some(Token::Last)),
+                (tasks[2], None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_game_is_reproducible() {
+        let template = TaskTemplate::new(3, &[Token::Absolute(1)]);
+        assert!(DealGenerator::new(4).game(&template).is_some());
+        assert_eq!(
+            DealGenerator::new(4).game(&template),
+            DealGenerator::new(4).game(&template)
+        );
+    }
+}
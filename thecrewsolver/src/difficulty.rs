@@ -0,0 +1,205 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::estimate::{estimate_tasks, EstimateOptions, EstimateReport};
+use crate::solver::{solve_each_play, GameState};
+
+const ROOT_WEIGHT: f64 = 0.4;
+const FORCED_WEIGHT: f64 = 0.2;
+const RANDOM_DEALS_WEIGHT: f64 = 0.4;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Difficulty {
+    /// 0 for a deal that cannot go wrong, 100 for one that cannot be won.
+    pub score: f64,
+    pub winnable: bool,
+    pub root_plays: usize,
+    pub winning_root_plays: usize,
+    /// The first trick (counting from 1) where only one play keeps the game winnable, along the
+    /// line that always takes the first winning play in generation order. This is a heuristic:
+    /// other winning lines may be forced sooner or later.
+    pub forced_trick: Option<u8>,
+    pub tricks: u8,
+    /// How often the same tasks can be won on random deals. Not estimated for a deal that can't
+    /// be won, whose score is 100 regardless.
+    pub random_deal_win_rate: Option<f64>,
+}
+
+impl Difficulty {
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    pub fn winnable(&self) -> bool {
+        self.winnable
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Rates a deal by combining how many opening plays still win, how soon the crew is forced into
+/// a single line of play, and how often the same tasks can be won on other deals. Returns `None`
+/// if solving the deal itself takes longer than `options.timeout`.
+pub fn rate(state: &GameState, options: &EstimateOptions) -> Option<Difficulty> {
+    let line = search_forced_line(state, options.timeout)?;
+    let random_deal_win_rate = (line.winning_root_plays > 0)
+        .then(|| EstimateReport::new(&estimate_tasks(state.tasks(), options)).win_rate());
+    Some(Difficulty::new(line, random_deal_win_rate))
+}
+
+struct ForcedLine {
+    root_plays: usize,
+    winning_root_plays: usize,
+    pub forced_trick: Option<u8>,
+    pub tricks: u8,
+}
+
+impl Difficulty {
+    fn new(line: ForcedLine, random_deal_win_rate: Option<f64>) -> Difficulty {
+        let winnable = line.winning_root_plays > 0;
+        let score = if let Some(win_rate) = random_deal_win_rate.filter(|_| winnable) {
+            let root = 1.0 - line.winning_root_plays as f64 / line.root_plays as f64;
+            let forced = match line.forced_trick {
+                Some(trick) => 1.0 - (trick - 1) as f64 / line.tricks as f64,
+                None => 0.0,
+            };
+            let random_deals = 1.0 - win_rate;
+            100.0
+                * (ROOT_WEIGHT * root + FORCED_WEIGHT * forced + RANDOM_DEALS_WEIGHT * random_deals)
+        } else {
+            100.0
+        };
+
+        Difficulty {
+            score,
+            winnable,
+            root_plays: line.root_plays,
+            winning_root_plays: line.winning_root_plays,
+            forced_trick: line.forced_trick,
+            tricks: line.tricks,
+            random_deal_win_rate,
+        }
+    }
+}
+
+/// Follows the first winning play of each trick until a trick has only one winning play. Which
+/// winning play comes first depends on `PlayGenerator`'s order, so the forced trick found is that
+/// of one winning line, not necessarily the earliest or latest of them all. Finding those exactly
+/// means solving every winning position, which is far too slow for a full deal.
+fn search_forced_line(state: &GameState, timeout: Duration) -> Option<ForcedLine> {
+    let deadline = Instant::now() + timeout;
+    let tricks = state.hands().iter().map(|h| h.num_set()).max().unwrap_or(0) as u8;
+
+    let mut line = ForcedLine {
+        root_plays: 0,
+        winning_root_plays: 0,
+        forced_trick: None,
+        tricks,
+    };
+    let mut state = state.clone();
+    for trick in 1..=tricks {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let plays = solve_each_play(&state, remaining)?.plays;
+        let num_plays = plays.len();
+        let winning = plays
+            .into_iter()
+            .filter(|(_, won)| *won)
+            .map(|(p, _)| p)
+            .collect::<Vec<_>>();
+
+        if trick == 1 {
+            line.root_plays = num_plays;
+            line.winning_root_plays = winning.len();
+        }
+
+        let Some(play) = winning.first() else {
+            break;
+        };
+        if winning.len() == 1 {
+            line.forced_trick = Some(trick);
+            break;
+        }
+        match state.after_trick(play) {
+            Some(next) if !next.is_won() => state = next,
+            _ => break,
+        }
+    }
+
+    Some(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Card::*, CardSet};
+    use crate::fixtures::toy_state;
+    use crate::tasks::{Task, TasksObjective};
+
+    fn options() -> EstimateOptions {
+        EstimateOptions {
+            deals: 4,
+            seed: 0,
+            timeout: Duration::from_secs(5),
+            threads: 1,
+        }
+    }
+
+    #[test]
+    fn test_forced_line() {
+        let line_for = |task| search_forced_line(&toy_state(task), Duration::from_secs(5)).unwrap();
+
+        let line = line_for(Task::new(1, Y7));
+        assert_eq!(line.root_plays, 4);
+        assert_eq!(line.winning_root_plays, 0);
+        assert_eq!(line.forced_trick, None);
+
+        let line = line_for(Task::new(0, Y2));
+        assert_eq!(line.winning_root_plays, 2);
+        assert_eq!(line.forced_trick, Some(2));
+
+        let line = line_for(Task::new(0, B9));
+        assert_eq!(line.winning_root_plays, 2);
+        assert_eq!(line.forced_trick, None);
+        assert_eq!(line.tricks, 2);
+    }
+
+    #[test]
+    fn test_unwinnable_scores_100() {
+        let state = GameState::new(
+            [
+                CardSet::from_cards(&[B1]),
+                CardSet::from_cards(&[B2]),
+                CardSet::from_cards(&[B3]),
+                CardSet::from_cards(&[B4]),
+            ],
+            TasksObjective::new(&[], &[], &[Task::new(0, B2)], None),
+            0,
+        );
+
+        let difficulty = rate(&state, &options()).unwrap();
+        assert!(!difficulty.winnable());
+        assert_eq!(difficulty.score(), 100.0);
+        assert_eq!(difficulty.random_deal_win_rate, None);
+    }
+
+    #[test]
+    fn test_score_range() {
+        let state = GameState::new(
+            [
+                CardSet::from_cards(&[B1]),
+                CardSet::from_cards(&[B2]),
+                CardSet::from_cards(&[B3]),
+                CardSet::from_cards(&[B4]),
+            ],
+            TasksObjective::new(&[], &[], &[Task::new(3, B2)], None),
+            0,
+        );
+
+        let difficulty = rate(&state, &options()).unwrap();
+        assert!(difficulty.winnable());
+        assert_eq!(difficulty.forced_trick, Some(1));
+        assert!((0.0..=100.0).contains(&difficulty.score()));
+    }
+}
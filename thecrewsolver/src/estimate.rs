@@ -0,0 +1,247 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::deal::{DealGenerator, TaskTemplate};
+use crate::parallel::parallel_map;
+use crate::player::NUM_PLAYERS;
+use crate::solver::{cache_limit_per_thread, GameState, Search, DEFAULT_CACHE_LIMIT};
+use crate::tasks::TasksObjective;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Won,
+    Lost,
+    TimedOut,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub seed: u64,
+    pub outcome: Outcome,
+    pub duration: Duration,
+}
+
+pub struct EstimateOptions {
+    pub deals: usize,
+    pub seed: u64,
+    pub timeout: Duration,
+    pub threads: usize,
+}
+
+/// Solves `options.deals` random deals of the template across `options.threads` threads. Deal `i`
+/// is generated from seed `options.seed + i`, so a run can be reproduced deal by deal.
+pub fn estimate(template: &TaskTemplate, options: &EstimateOptions) -> Vec<Sample> {
+    estimate_games(options, |seed| {
+        DealGenerator::new(seed)
+            .game(template)
+            .expect("templates always produce 4 player games")
+    })
+}
+
+/// Like `estimate`, but keeps the given tasks and only deals new hands.
+pub fn estimate_tasks(tasks: &TasksObjective, options: &EstimateOptions) -> Vec<Sample> {
+    estimate_games(options, |seed| {
+        let deal = DealGenerator::new(seed)
+            .deal(NUM_PLAYERS)
+            .expect("4 players is a valid player count");
+        GameState::new(deal.to_hands().unwrap(), tasks.clone(), deal.commander())
+    })
+}
+
+fn estimate_games<F>(options: &EstimateOptions, make_game: F) -> Vec<Sample>
+where
+    F: Fn(u64) -> GameState + Sync,
+{
+    let cache_limit = cache_limit_per_thread(DEFAULT_CACHE_LIMIT, options.threads);
+    parallel_map(options.deals, options.threads, |i| {
+        let seed = options.seed.wrapping_add(i as u64);
+        sample(&make_game(seed), seed, options.timeout, cache_limit)
+    })
+}
+
+fn sample(state: &GameState, seed: u64, timeout: Duration, cache_limit: usize) -> Sample {
+    let start = Instant::now();
+    let mut search = Search::new(Some(start + timeout)).with_cache_limit(cache_limit);
+    let outcome = match search.solve(state) {
+        Some(true) => Outcome::Won,
+        Some(false) => Outcome::Lost,
+        None => Outcome::TimedOut,
+    };
+
+    Sample {
+        seed,
+        outcome,
+        duration: start.elapsed(),
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct EstimateReport {
+    pub deals: usize,
+    pub won: usize,
+    pub lost: usize,
+    pub timed_out: usize,
+    /// Wins out of the deals that finished solving.
+    pub win_rate: f64,
+    pub duration: DurationSummary,
+}
+
+/// Solve times in milliseconds.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DurationSummary {
+    pub min: u128,
+    pub median: u128,
+    pub p90: u128,
+    pub p99: u128,
+    pub max: u128,
+    pub mean: f64,
+}
+
+impl EstimateReport {
+    pub fn new(samples: &[Sample]) -> Self {
+        let count = |outcome| samples.iter().filter(|s| s.outcome == outcome).count();
+        let won = count(Outcome::Won);
+        let lost = count(Outcome::Lost);
+        let solved = won + lost;
+
+        Self {
+            deals: samples.len(),
+            won,
+            lost,
+            timed_out: count(Outcome::TimedOut),
+            win_rate: if solved > 0 {
+                won as f64 / solved as f64
+            } else {
+                0.0
+            },
+            duration: DurationSummary::new(samples),
+        }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.win_rate
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+impl DurationSummary {
+    pub fn new(samples: &[Sample]) -> Self {
+        let mut millis = samples
+            .iter()
+            .map(|s| s.duration.as_millis())
+            .collect::<Vec<_>>();
+        millis.sort();
+
+        let percentile = |p: usize| {
+            millis
+                .get((millis.len() * p / 100).min(millis.len().saturating_sub(1)))
+                .copied()
+                .unwrap_or(0)
+        };
+
+        Self {
+            min: percentile(0),
+            median: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: millis.last().copied().unwrap_or(0),
+            mean: if millis.is_empty() {
+                0.0
+            } else {
+                millis.iter().sum::<u128>() as f64 / millis.len() as f64
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card;
+    use crate::tasks::Task;
+
+    fn sample(outcome: Outcome, millis: u64) -> Sample {
+        Sample {
+            seed: 0,
+            outcome,
+            duration: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn test_estimate_is_reproducible() {
+        let template = TaskTemplate::new(1, &[]);
+        let options = EstimateOptions {
+            deals: 6,
+            seed: 10,
+            timeout: Duration::from_secs(5),
+            threads: 3,
+        };
+
+        let samples = estimate(&template, &options);
+        assert_eq!(
+            samples.iter().map(|s| s.seed).collect::<Vec<_>>(),
+            vec![10, 11, 12, 13, 14, 15]
+        );
+        assert_eq!(
+            samples.iter().map(|s| s.outcome).collect::<Vec<_>>(),
+            estimate(&template, &options)
+                .iter()
+                .map(|s| s.outcome)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_estimate_tasks_keeps_tasks() {
+        let tasks = TasksObjective::new(&[], &[], &[Task::new(1, Card::B3)], None);
+        let options = EstimateOptions {
+            deals: 4,
+            seed: 0,
+            timeout: Duration::from_secs(5),
+            threads: 2,
+        };
+
+        let samples = estimate_tasks(&tasks, &options);
+        assert_eq!(samples.len(), 4);
+        assert!(samples.iter().all(|s| s.outcome != Outcome::TimedOut));
+    }
+
+    #[test]
+    fn test_report() {
+        let report = EstimateReport::new(&[
+            sample(Outcome::Won, 1),
+            sample(Outcome::Won, 2),
+            sample(Outcome::Lost, 3),
+            sample(Outcome::TimedOut, 10),
+        ]);
+
+        assert_eq!(
+            report,
+            EstimateReport {
+                deals: 4,
+                won: 2,
+                lost: 1,
+                timed_out: 1,
+                win_rate: 2.0 / 3.0,
+                duration: DurationSummary {
+                    min: 1,
+                    median: 3,
+                    p90: 10,
+                    p99: 10,
+                    max: 10,
+                    mean: 4.0,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = EstimateReport::new(&[]);
+        assert_eq!(report.win_rate(), 0.0);
+    }
+}
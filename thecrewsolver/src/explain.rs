@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use crate::solver::{solve_within, GameState};
+use crate::tasks::{Task, TasksObjective, Token};
+
+/// Why a game cannot be won: a minimal set (no task can be removed) of tasks and tokens that is
+/// already unwinnable on its own.
+#[derive(Debug, PartialEq)]
+pub struct Explanation {
+    /// The tasks that still make the game unwinnable. Removing any of them, or any of their
+    /// tokens, lets the crew win.
+    pub core: Vec<(Task, Option<Token>)>,
+    /// Tasks that play no part in losing the game.
+    pub dropped_tasks: Vec<(Task, Option<Token>)>,
+    /// Tasks in `core` whose tokens were removed without making the game winnable.
+    pub dropped_tokens: Vec<(Task, Token)>,
+    /// False if some solve timed out, in which case the core is still unwinnable but may not be
+    /// as small as it could be.
+    pub minimal: bool,
+}
+
+/// Shrinks the tasks of an unwinnable game with delta debugging: first removing tasks, then
+/// removing tokens from the tasks that are left. Every solve gets `timeout`; a solve that times out
+/// is treated as winnable, so the core is always proven unwinnable. Returns `None` unless the full
+/// game is shown to be unwinnable.
+pub fn explain(state: &GameState, timeout: Duration) -> Option<Explanation> {
+    let tasks = state.tasks().to_tokens();
+    let mut minimal = true;
+    let mut is_lost = |tasks: &[(Task, Option<Token>)]| {
+        let objective =
+            TasksObjective::from_tokens(tasks)?.with_tricks_played(state.tasks().tricks_played());
+        let result = solve_within(
+            &GameState::new(*state.hands(), objective, state.curr_leader()),
+            timeout,
+        );
+        minimal &= result.is_some();
+        Some(result == Some(false))
+    };
+
+    if is_lost(&tasks) != Some(true) {
+        return None;
+    }
+
+    let mut core = ddmin(tasks.clone(), |subset| is_lost(subset) == Some(true));
+    let dropped_tasks = tasks.into_iter().filter(|t| !core.contains(t)).collect();
+
+    // Dropping a token only makes the game easier, so one pass leaves every remaining token
+    // necessary.
+    let mut dropped_tokens = vec![];
+    for i in 0..core.len() {
+        let (task, Some(token)) = core[i] else {
+            continue;
+        };
+        core[i].1 = None;
+        if is_lost(&core) == Some(true) {
+            dropped_tokens.push((task, token));
+        } else {
+            core[i].1 = Some(token);
+        }
+    }
+
+    Some(Explanation {
+        core,
+        dropped_tasks,
+        dropped_tokens,
+        minimal,
+    })
+}
+
+/// Removal-only delta debugging: returns a subset of `items` for which `fails` still holds and
+/// from which no single item can be removed.
+fn ddmin<T: Clone>(mut items: Vec<T>, mut fails: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut chunks = 2;
+    while items.len() >= 2 {
+        let chunk_len = items.len().div_ceil(chunks);
+        let reduced = (0..items.len()).step_by(chunk_len).find_map(|start| {
+            let end = (start + chunk_len).min(items.len());
+            let complement = [&items[..start], &items[end..]].concat();
+            fails(&complement).then_some(complement)
+        });
+
+        match reduced {
+            Some(complement) => {
+                items = complement;
+                chunks = (chunks - 1).max(2);
+            }
+            None if chunks >= items.len() => break,
+            None => chunks = (chunks * 2).min(items.len()),
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Card::*, CardSet};
+    use crate::fixtures::endgame_state;
+
+    #[test]
+    fn test_ddmin() {
+        let items = (0..10).collect::<Vec<_>>();
+        assert_eq!(
+            ddmin(items.clone(), |s| s.contains(&3) && s.contains(&7)),
+            vec![3, 7]
+        );
+        assert_eq!(ddmin(items, |s| s.contains(&9)), vec![9]);
+    }
+
+    #[test]
+    fn test_explain() {
+        let hands = [
+            CardSet::from_cards(&[B1, B9]),
+            CardSet::from_cards(&[B2, Y2]),
+            CardSet::from_cards(&[Y3, Y7]),
+            CardSet::from_cards(&[B4, Y4]),
+        ];
+        // Player 1 can never win Y7, the other tasks are easy.
+        let tasks = TasksObjective::from_tokens(&[
+            (Task::new(0, B9), Some(Token::Absolute(1))),
+            (Task::new(1, Y7), Some(Token::Absolute(2))),
+            (Task::new(0, B2), None),
+        ])
+        .unwrap();
+        let state = GameState::new(hands, tasks, 0);
+
+        let explanation = explain(&state, Duration::from_secs(5)).unwrap();
+        assert_eq!(explanation.core, vec![(Task::new(1, Y7), None)]);
+        assert_eq!(
+            explanation.dropped_tokens,
+            vec![(Task::new(1, Y7), Token::Absolute(2))]
+        );
+        assert_eq!(explanation.dropped_tasks.len(), 2);
+        assert!(explanation.minimal);
+    }
+
+    #[test]
+    fn test_explain_keeps_needed_tokens() {
+        let hands = [
+            CardSet::from_cards(&[B1, B9]),
+            CardSet::from_cards(&[B2, Y2]),
+            CardSet::from_cards(&[Y3, Y7]),
+            CardSet::from_cards(&[B4, Y4]),
+        ];
+        // Y2 can only be won in the second trick, so it can't come before B9, whatever token B9
+        // has.
+        let tasks = TasksObjective::from_tokens(&[
+            (Task::new(0, B9), Some(Token::Absolute(2))),
+            (Task::new(0, Y2), Some(Token::Absolute(1))),
+        ])
+        .unwrap();
+        let state = GameState::new(hands, tasks, 0);
+
+        let explanation = explain(&state, Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            explanation.core,
+            vec![
+                (Task::new(0, Y2), Some(Token::Absolute(1))),
+                (Task::new(0, B9), None)
+            ]
+        );
+        assert_eq!(
+            explanation.dropped_tokens,
+            vec![(Task::new(0, B9), Token::Absolute(2))]
+        );
+        assert!(explanation.dropped_tasks.is_empty());
+    }
+
+    #[test]
+    fn test_explain_winnable() {
+        let hands = [
+            CardSet::from_cards(&[B1]),
+            CardSet::from_cards(&[B2]),
+            CardSet::from_cards(&[B3]),
+            CardSet::from_cards(&[B4]),
+        ];
+        let tasks = TasksObjective::new(&[], &[], &[Task::new(3, B2)], None);
+        assert_eq!(
+            explain(&GameState::new(hands, tasks, 0), Duration::from_secs(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_explain_endgame() {
+        // P1 holds R4, so P2 can never win it; P4 can win G9 by following a green lead.
+        let state = endgame_state(0, 4, &[Task::new(3, G9), Task::new(1, R4)]);
+        let explanation = explain(&state, Duration::from_secs(30)).unwrap();
+        assert_eq!(explanation.core, vec![(Task::new(1, R4), None)]);
+        assert_eq!(explanation.dropped_tasks, vec![(Task::new(3, G9), None)]);
+        assert!(explanation.minimal);
+    }
+}
@@ -0,0 +1,376 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::cli::{cards_by_seat, to_state, State};
+use crate::deal::{DealGenerator, TaskTemplate};
+use crate::parallel::parallel_map;
+use crate::solver::{cache_limit_per_thread, Search, DEFAULT_CACHE_LIMIT};
+
+/// Records are solved and written in chunks of this size, so an interrupted export loses at most
+/// one chunk of work.
+const CHUNK_RECORDS: usize = 64;
+
+const CSV_HEADER: &str = "index,seed,leader,hands,tasks,winnable,winning_plays,nodes,duration_ms";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Jsonl,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jsonl" => Ok(Format::Jsonl),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format `{}`", s)),
+        }
+    }
+}
+
+pub struct ExportOptions {
+    pub records: usize,
+    pub seed: u64,
+    pub timeout: Duration,
+    pub threads: usize,
+    pub format: Format,
+    /// Written into each record so it can be fed back to the solver as a mission.
+    pub mission: Option<u8>,
+}
+
+/// The options an export was started with, kept next to it so a resumed export can check that it
+/// continues the same sequence of records.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct ExportSettings {
+    seed: u64,
+    format: String,
+    mission: Option<u8>,
+    tasks: usize,
+    tokens: Vec<String>,
+}
+
+impl ExportSettings {
+    fn new(template: &TaskTemplate, options: &ExportOptions) -> ExportSettings {
+        ExportSettings {
+            seed: options.seed,
+            format: format!("{:?}", options.format).to_lowercase(),
+            mission: options.mission,
+            tasks: template.num_tasks,
+            tokens: template.tokens.iter().map(|t| format!("{:?}", t)).collect(),
+        }
+    }
+}
+
+/// Where the settings for the export to `path` are kept: the same name with `.options.json` added.
+pub fn settings_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".options.json");
+    PathBuf::from(name)
+}
+
+/// One solved deal. The JSON form is also a valid solver input.
+#[derive(Serialize)]
+pub struct Record {
+    index: usize,
+    seed: u64,
+    #[serde(flatten)]
+    game: State,
+    /// `None` if solving took longer than the timeout.
+    winnable: Option<bool>,
+    /// Every first trick that keeps the game winnable, as the card played by each seat.
+    winning_plays: Vec<Vec<String>>,
+    nodes: Option<u64>,
+    duration_ms: u128,
+}
+
+impl Record {
+    pub fn winnable(&self) -> Option<bool> {
+        self.winnable
+    }
+
+    fn to_line(&self, format: Format) -> String {
+        match format {
+            Format::Jsonl => serde_json::to_string(self).unwrap(),
+            Format::Csv => self.to_csv(),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let hands = self
+            .game
+            .hands
+            .iter()
+            .map(|h| h.join(" "))
+            .collect::<Vec<_>>();
+        let tasks = self
+            .game
+            .tasks
+            .iter()
+            .map(|t| match t.task_type.as_str() {
+                "absolute" => format!("{}:{}:A{}", t.player_num, t.card, t.order),
+                "relative" => format!("{}:{}:R{}", t.player_num, t.card, t.order),
+                "last" => format!("{}:{}:L", t.player_num, t.card),
+                _ => format!("{}:{}", t.player_num, t.card),
+            })
+            .collect::<Vec<_>>();
+        let winning_plays = self
+            .winning_plays
+            .iter()
+            .map(|p| p.join(" "))
+            .collect::<Vec<_>>();
+
+        format!(
+            "{},{},{},{},{},{},{},{},{}",
+            self.index,
+            self.seed,
+            self.game.curr_leader,
+            hands.join("|"),
+            tasks.join(" "),
+            self.winnable.map_or(String::new(), |w| w.to_string()),
+            winning_plays.join("|"),
+            self.nodes.map_or(String::new(), |n| n.to_string()),
+            self.duration_ms
+        )
+    }
+}
+
+/// Deals and solves record `index`, generated from seed `options.seed + index`.
+pub fn record(template: &TaskTemplate, options: &ExportOptions, index: usize) -> Record {
+    let seed = options.seed.wrapping_add(index as u64);
+    let state = DealGenerator::new(seed)
+        .game(template)
+        .expect("templates always produce 4 player games");
+
+    let start = Instant::now();
+    let results = Search::new(Some(start + options.timeout))
+        .with_cache_limit(cache_limit_per_thread(DEFAULT_CACHE_LIMIT, options.threads))
+        .solve_each_play(&state);
+    let duration_ms = start.elapsed().as_millis();
+
+    let winning_plays = results.as_ref().map_or(vec![], |r| {
+        r.plays
+            .iter()
+            .filter(|(_, won)| *won)
+            .map(|(play, _)| cards_by_seat(&state, play))
+            .collect()
+    });
+
+    Record {
+        index,
+        seed,
+        game: to_state(&state, options.mission),
+        winnable: results
+            .as_ref()
+            .map(|r| r.plays.iter().any(|(_, won)| *won)),
+        winning_plays,
+        nodes: results.map(|r| r.nodes),
+        duration_ms,
+    }
+}
+
+/// Writes `options.records` records to `path`. Records already in the file are kept, so an
+/// interrupted export picks up where it stopped; a partly written last line is dropped. The
+/// options are saved to `settings_path(path)`, and resuming with different ones is refused rather
+/// than mixing records from two exports. Returns the number of records written by this call.
+pub fn export(path: &Path, template: &TaskTemplate, options: &ExportOptions) -> io::Result<usize> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+
+    let (mut done, complete_len) = count_complete_lines(&file)?;
+    let settings = ExportSettings::new(template, options);
+    if done == 0 {
+        fs::write(
+            settings_path(path),
+            serde_json::to_string(&settings).unwrap(),
+        )?;
+    } else {
+        check_settings(path, &settings)?;
+    }
+    file.set_len(complete_len)?;
+    file.seek(SeekFrom::End(0))?;
+
+    if options.format == Format::Csv {
+        if done == 0 {
+            writeln!(file, "{}", CSV_HEADER)?;
+        } else {
+            done -= 1;
+        }
+    }
+
+    let start = done;
+    while done < options.records {
+        let chunk = CHUNK_RECORDS.min(options.records - done);
+        let records = parallel_map(chunk, options.threads, |i| {
+            record(template, options, done + i).to_line(options.format)
+        });
+        for line in records {
+            writeln!(file, "{}", line)?;
+        }
+        file.flush()?;
+        done += chunk;
+    }
+
+    Ok(done.saturating_sub(start))
+}
+
+fn check_settings(path: &Path, settings: &ExportSettings) -> io::Result<()> {
+    let saved = fs::read_to_string(settings_path(path))
+        .ok()
+        .and_then(|s| serde_json::from_str::<ExportSettings>(&s).ok());
+    match saved {
+        Some(saved) if saved == *settings => Ok(()),
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} was started with different options; use them again or write to another file",
+                path.display()
+            ),
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} has records but no readable {}, so it can't be resumed",
+                path.display(),
+                settings_path(path).display()
+            ),
+        )),
+    }
+}
+
+/// Returns the number of newline-terminated lines and the byte length they take up.
+fn count_complete_lines(file: &File) -> io::Result<(usize, u64)> {
+    let mut reader = BufReader::new(file);
+    let mut line = vec![];
+    let (mut lines, mut len) = (0, 0);
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 || line.last() != Some(&b'\n') {
+            return Ok((lines, len));
+        }
+        lines += 1;
+        len += read as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse;
+    use std::fs;
+
+    fn options(format: Format, records: usize) -> ExportOptions {
+        ExportOptions {
+            records,
+            seed: 1,
+            timeout: Duration::from_secs(5),
+            threads: 2,
+            format,
+            mission: None,
+        }
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("thecrewsolver-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(settings_path(&path));
+        path
+    }
+
+    #[test]
+    fn test_record() {
+        let template = TaskTemplate::new(1, &[]);
+        let options = options(Format::Jsonl, 1);
+        let record = record(&template, &options, 2);
+
+        assert_eq!(record.seed, 3);
+        assert_eq!(record.winnable, Some(!record.winning_plays.is_empty()));
+        assert!(record.nodes.unwrap() > 0);
+        for play in &record.winning_plays {
+            assert_eq!(play.len(), 4);
+        }
+
+        let line = record.to_line(Format::Jsonl);
+        assert_eq!(parse(&line).ok(), DealGenerator::new(3).game(&template));
+    }
+
+    #[test]
+    fn test_export_is_resumable() {
+        let template = TaskTemplate::new(1, &[]);
+        for format in [Format::Jsonl, Format::Csv] {
+            let full = temp_path("full");
+            assert_eq!(export(&full, &template, &options(format, 3)).unwrap(), 3);
+
+            let resumed = temp_path("resumed");
+            assert_eq!(export(&resumed, &template, &options(format, 1)).unwrap(), 1);
+            let mut file = OpenOptions::new().append(true).open(&resumed).unwrap();
+            write!(file, "{{\"partial").unwrap();
+            assert_eq!(export(&resumed, &template, &options(format, 3)).unwrap(), 2);
+            assert_eq!(export(&resumed, &template, &options(format, 3)).unwrap(), 0);
+
+            // Everything but the timing, which is the last field in both formats.
+            let strip = |path| {
+                fs::read_to_string(path)
+                    .unwrap()
+                    .lines()
+                    .map(|l| l.rsplit_once(',').map_or(l, |(a, _)| a).to_string())
+                    .collect::<Vec<_>>()
+            };
+            let lines = strip(&full);
+            assert_eq!(lines, strip(&resumed));
+            let header = usize::from(format == Format::Csv);
+            assert_eq!(lines.len(), 3 + header);
+
+            for path in [full, resumed] {
+                fs::remove_file(settings_path(&path)).unwrap();
+                fs::remove_file(path).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_resume_with_other_options() {
+        let template = TaskTemplate::new(1, &[]);
+        let path = temp_path("other-options");
+        export(&path, &template, &options(Format::Jsonl, 1)).unwrap();
+
+        let other_seed = ExportOptions {
+            seed: 2,
+            ..options(Format::Jsonl, 2)
+        };
+        assert!(export(&path, &template, &other_seed).is_err());
+        assert!(export(&path, &template, &options(Format::Csv, 2)).is_err());
+        assert!(export(
+            &path,
+            &TaskTemplate::new(2, &[]),
+            &options(Format::Jsonl, 2)
+        )
+        .is_err());
+        assert_eq!(
+            export(&path, &template, &options(Format::Jsonl, 2)).unwrap(),
+            1
+        );
+
+        fs::remove_file(settings_path(&path)).unwrap();
+        assert!(export(&path, &template, &options(Format::Jsonl, 3)).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!("jsonl".parse(), Ok(Format::Jsonl));
+        assert_eq!("csv".parse(), Ok(Format::Csv));
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use crate::card::{Card, Card::*, CardSet};
+use crate::deal::{DealGenerator, TaskTemplate};
+use crate::solver::{GameState, Search};
+use crate::tasks::{Task, TasksObjective};
+
+/// Four two-card hands, led by player 0, with `task` as the only task. Player 0 can win Y2 or
+/// B9, but Y7 can only be won by player 2.
+pub(crate) fn toy_state(task: Task) -> GameState {
+    GameState::new(
+        [
+            CardSet::from_cards(&[B1, B9]),
+            CardSet::from_cards(&[B2, Y2]),
+            CardSet::from_cards(&[Y3, Y7]),
+            CardSet::from_cards(&[B4, Y4]),
+        ],
+        TasksObjective::new(&[], &[], &[task], None),
+        0,
+    )
+}
+
+/// A whole game dealt from `seed` with `num_tasks` tasks without tokens, led by the commander.
+pub(crate) fn dealt_state(seed: u64, num_tasks: usize) -> GameState {
+    DealGenerator::new(seed)
+        .game(&TaskTemplate::new(num_tasks, &[]))
+        .unwrap()
+}
+
+/// The game dealt from `seed` played down to its last `tricks_left` tricks, always taking the
+/// first trick the generator gives, with `tasks` set for the rest of the game. Small enough to
+/// prove lost quickly, unlike a whole deal.
+pub(crate) fn endgame_state(seed: u64, tricks_left: usize, tasks: &[Task]) -> GameState {
+    let mut state = dealt_state(seed, 0);
+    while state.hands()[0].num_set() as usize > tricks_left {
+        let play = state.plays().next().unwrap();
+        state = state.after_trick(&play).unwrap();
+    }
+    GameState::new(
+        *state.hands(),
+        TasksObjective::new(&[], &[], tasks, None),
+        state.curr_leader(),
+    )
+}
+
+/// A winning line for a winnable `state`, each trick starting with the leader's card.
+pub(crate) fn winning_line(state: &GameState) -> Vec<Vec<Card>> {
+    let mut search = Search::new(None);
+    let mut state = state.clone();
+    let mut tricks = vec![];
+    while !state.is_won() {
+        let play = state
+            .plays()
+            .find(|play| search.solve_play(&state, play) == Some(true))
+            .unwrap();
+        tricks.push(state.cards_by_seat(&play).iter().map(|(_, c)| *c).collect());
+        state = state.after_trick(&play).unwrap();
+    }
+    tricks
+}
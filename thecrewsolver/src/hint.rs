@@ -0,0 +1,265 @@
+use std::time::{Duration, Instant};
+
+use crate::card::{Card, CardSet};
+use crate::play::PlayGenerator;
+use crate::player::{PlayerIndex, NUM_PLAYERS};
+use crate::solver::{GameState, Search};
+
+/// The position at the start of the current trick, with the cards already played in it (in order,
+/// starting with the leader) back in their players' hands. Returns `None` if those cards couldn't
+/// have been played: too many of them, one is still held by someone, or a player didn't follow
+/// suit.
+pub fn trick_start(state: &GameState, current_trick: &[Card]) -> Option<GameState> {
+    (current_trick.len() < NUM_PLAYERS).then_some(())?;
+
+    let mut hands = *state.hands();
+    let lead_suit = current_trick
+        .first()
+        .map_or(CardSet::EMPTY, |c| CardSet::from_card(*c).get_suit());
+    for (i, card) in current_trick.iter().enumerate() {
+        let card = CardSet::from_card(*card);
+        if hands.iter().any(|h| h.overlaps_with(card)) {
+            return None;
+        }
+
+        let hand = &mut hands[seat_after(state.curr_leader(), i)];
+        if i > 0 && !lead_suit.overlaps_with(card) && lead_suit.overlaps_with(*hand) {
+            return None;
+        }
+        *hand = hand.add(card);
+    }
+
+    Some(GameState::new(
+        hands,
+        state.tasks().clone(),
+        state.curr_leader(),
+    ))
+}
+
+/// Whose turn it is, given the cards already played in the current trick.
+pub fn seat_to_move(state: &GameState, current_trick: &[Card]) -> PlayerIndex {
+    seat_after(state.curr_leader(), current_trick.len()) as PlayerIndex
+}
+
+/// Which of `seat`'s legal cards keep the game winnable, given the cards already played in the
+/// current trick. Every card the trick so far allows the seat to play is reported, so the answer
+/// can be shown to that player without giving away anyone else's hand. Returns `None` if the
+/// timeout passed.
+///
+/// Only the seat whose turn it is (see `seat_to_move`) can ask, in a trick that could have been
+/// played (see `trick_start`); anything else panics.
+pub fn safe_cards(
+    state: &GameState,
+    current_trick: &[Card],
+    seat: PlayerIndex,
+    timeout: Duration,
+) -> Option<Vec<(Card, bool)>> {
+    let mut search = Search::new(Some(Instant::now() + timeout));
+    safe_cards_in(&mut search, state, current_trick, seat)
+}
+
+/// Like `safe_cards`, but with a search of the caller's, which can be cancelled. Returns `None`
+/// if the search gives up.
+pub fn safe_cards_in(
+    search: &mut Search,
+    state: &GameState,
+    current_trick: &[Card],
+    seat: PlayerIndex,
+) -> Option<Vec<(Card, bool)>> {
+    let (start, plays) = completions(state, current_trick, seat)
+        .expect("only the seat to move can ask, in a trick that could have been played");
+
+    let mut results: Vec<(Card, bool)> = vec![];
+    for play in plays {
+        let card = (play.cards() & start.hands()[seat as usize]).cards()[0];
+        let i = match results.iter().position(|(c, _)| *c == card) {
+            Some(i) if results[i].1 => continue,
+            Some(i) => i,
+            None => {
+                results.push((card, false));
+                results.len() - 1
+            }
+        };
+        results[i].1 = search.solve_play(&start, &play)?;
+    }
+
+    results.sort_by_key(|(card, _)| *card as u8);
+    Some(results)
+}
+
+/// Whether the crew can still win with `current_trick` under way, which is whether the player
+/// to move has a safe card. Returns `None` if the search gives up. The trick must be one that could
+/// have been played, as `cli::parse_position` makes sure.
+pub fn solve_position(
+    search: &mut Search,
+    state: &GameState,
+    current_trick: &[Card],
+) -> Option<bool> {
+    if current_trick.is_empty() {
+        return search.solve(state);
+    }
+    let seat = seat_to_move(state, current_trick);
+    let cards = safe_cards_in(search, state, current_trick, seat)?;
+    Some(cards.iter().any(|(_, safe)| *safe))
+}
+
+/// The cards `seat` may play next, without solving anything. Like `safe_cards`, only for the seat
+/// whose turn it is.
+pub fn legal_cards(
+    state: &GameState,
+    current_trick: &[Card],
+    seat: PlayerIndex,
+) -> Option<Vec<Card>> {
+    let (start, plays) = completions(state, current_trick, seat)?;
+    let cards = plays.fold(CardSet::EMPTY, |a, p| a | p.cards());
+    Some((cards & start.hands()[seat as usize]).cards())
+}
+
+/// The position at the start of the trick and every way the trick in progress can be finished,
+/// if it is `seat`'s turn.
+fn completions(
+    state: &GameState,
+    current_trick: &[Card],
+    seat: PlayerIndex,
+) -> Option<(GameState, PlayGenerator)> {
+    ((seat as usize) < NUM_PLAYERS).then_some(())?;
+    let start = trick_start(state, current_trick)?;
+    let leader = state.curr_leader();
+    let position = (seat as usize + NUM_PLAYERS - leader as usize) % NUM_PLAYERS;
+    (position == current_trick.len()).then_some(())?;
+
+    // Players who already played can only have played the card they did.
+    let mut hands = *start.hands();
+    for (i, card) in current_trick.iter().enumerate() {
+        hands[seat_after(leader, i)] = CardSet::from_card(*card);
+    }
+    Some((start, PlayGenerator::new(&hands, leader)))
+}
+
+fn seat_after(leader: PlayerIndex, offset: usize) -> usize {
+    (leader as usize + offset) % NUM_PLAYERS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::toy_state;
+    use crate::tasks::{Task, TasksObjective};
+
+    #[test]
+    fn test_safe_cards_at_trick_start() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            safe_cards(&toy_state(Task::new(0, Y2)), &[], 0, timeout),
+            Some(vec![(B1, false), (B9, true)])
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_safe_cards_out_of_turn() {
+        // Only the leader can ask before anyone has played.
+        safe_cards(&toy_state(Task::new(0, Y2)), &[], 1, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_safe_cards_mid_trick() {
+        let timeout = Duration::from_secs(5);
+        let state = |third_hand: &[Card]| {
+            GameState::new(
+                [
+                    CardSet::from_cards(&[B9]),
+                    CardSet::from_cards(&[Y2]),
+                    CardSet::from_cards(third_hand),
+                    CardSet::from_cards(&[B4, Y4]),
+                ],
+                TasksObjective::new(&[], &[], &[Task::new(3, B2)], None),
+                0,
+            )
+        };
+        assert_eq!(
+            safe_cards(&state(&[Y3, Y7]), &[B1, B2], 2, timeout),
+            Some(vec![(Y3, true), (Y7, true)])
+        );
+        assert_eq!(seat_to_move(&state(&[Y3, Y7]), &[B1, B2]), 2);
+
+        let state = state(&[Y7]);
+        assert_eq!(
+            safe_cards(&state, &[B1, B2, Y3], 3, timeout),
+            Some(vec![(B4, true)])
+        );
+        assert_eq!(seat_to_move(&state, &[B1, B2, Y3]), 3);
+        assert_eq!(seat_to_move(&state, &[]), 0);
+    }
+
+    #[test]
+    fn test_solve_position() {
+        let mut search = Search::new(None);
+        let state = toy_state(Task::new(0, Y2));
+        assert_eq!(solve_position(&mut search, &state, &[]), Some(true));
+        let after = GameState::new(
+            [
+                CardSet::from_cards(&[B9]),
+                CardSet::from_cards(&[B2, Y2]),
+                CardSet::from_cards(&[Y3, Y7]),
+                CardSet::from_cards(&[B4, Y4]),
+            ],
+            state.tasks().clone(),
+            0,
+        );
+        // Leading B1 lets P4 win the trick and lead Y4, which P3 wins.
+        assert_eq!(solve_position(&mut search, &after, &[B1]), Some(false));
+    }
+
+    #[test]
+    fn test_legal_cards() {
+        let state = toy_state(Task::new(0, Y2));
+        assert_eq!(legal_cards(&state, &[], 0), Some(vec![B1, B9]));
+        assert_eq!(legal_cards(&state, &[], 2), None);
+        let after = GameState::new(
+            [
+                CardSet::from_cards(&[B9]),
+                CardSet::from_cards(&[B2, Y2]),
+                CardSet::from_cards(&[Y3, Y7]),
+                CardSet::from_cards(&[B4, Y4]),
+            ],
+            state.tasks().clone(),
+            0,
+        );
+        assert_eq!(legal_cards(&after, &[Y4], 1), None);
+        assert_eq!(legal_cards(&after, &[B1], 1), Some(vec![B2]));
+    }
+
+    #[test]
+    fn test_trick_start() {
+        let state = toy_state(Task::new(0, Y2));
+        assert_eq!(trick_start(&state, &[]), Some(state.clone()));
+        assert_eq!(trick_start(&state, &[B9]), None);
+
+        let after = GameState::new(
+            [
+                CardSet::from_cards(&[B9]),
+                CardSet::from_cards(&[B2]),
+                CardSet::from_cards(&[Y3, Y7]),
+                CardSet::from_cards(&[B4, Y4]),
+            ],
+            state.tasks().clone(),
+            0,
+        );
+        // B1 was led, so seat 1 can't play Y2 while holding B2.
+        assert_eq!(trick_start(&after, &[B1, Y2]), None);
+
+        let after = GameState::new(
+            [
+                CardSet::from_cards(&[B9]),
+                CardSet::from_cards(&[Y2]),
+                CardSet::from_cards(&[Y3, Y7]),
+                CardSet::from_cards(&[B4, Y4]),
+            ],
+            state.tasks().clone(),
+            0,
+        );
+        assert_eq!(trick_start(&after, &[B1, B2]), Some(state));
+    }
+}
@@ -0,0 +1,393 @@
+use crate::bench::BenchReport;
+use crate::card::{Card, CardSet};
+use crate::consistency::Issue;
+use crate::difficulty::Difficulty;
+use crate::estimate::{DurationSummary, EstimateReport};
+use crate::explain::Explanation;
+use crate::invariants::Invariants;
+use crate::play::Play;
+use crate::player::PlayerIndex;
+use crate::post_mortem::PostMortem;
+use crate::record::Position;
+use crate::robustness::RobustnessReport;
+use crate::solver::{GameState, PlayResults};
+use crate::tasks::{Task, Token};
+use crate::verify::Verification;
+
+/// Most winning first tricks listed by `describe_analysis`.
+const MAX_LISTED_PLAYS: usize = 20;
+
+/// One line per hand, then one line per task. Like all the text output, players are counted from
+/// 1 as they are at the table.
+pub fn describe_deal(
+    hands: &[CardSet],
+    tasks: &[(Task, Option<Token>)],
+    curr_leader: PlayerIndex,
+) -> String {
+    let mut lines = vec![];
+    for (player, hand) in hands.iter().enumerate() {
+        let lead = if player as PlayerIndex == curr_leader {
+            " (leads)"
+        } else {
+            ""
+        };
+        lines.push(format!("P{}{}: {}", player + 1, lead, cards(&hand.cards())));
+    }
+    if tasks.is_empty() {
+        lines.push("No tasks".to_string());
+    }
+    for (task, token) in tasks {
+        lines.push(format!("Task: {}", task_text(task, *token)));
+    }
+    lines.join("\n")
+}
+
+pub fn describe_state(state: &GameState) -> String {
+    describe_deal(
+        state.hands(),
+        &state.tasks().to_tokens(),
+        state.curr_leader(),
+    )
+}
+
+/// The game at a point in a record, with the cards of the trick in progress.
+pub fn describe_position(position: &Position) -> String {
+    let mut lines = vec![describe_state(&position.state)];
+    if !position.current_trick.is_empty() {
+        lines.push(format!(
+            "Trick so far: {}, P{} to play",
+            cards(&position.current_trick),
+            position.seat() + 1
+        ));
+    }
+    if let Some(trick) = position.failed_trick {
+        lines.push(format!("A task was failed in trick {}", trick));
+    }
+    lines.join("\n")
+}
+
+/// `result` is `None` if the solver timed out.
+pub fn describe_solve(result: Option<bool>, millis: u128) -> String {
+    match result {
+        Some(true) => format!("Winnable ({} ms)", millis),
+        Some(false) => format!("Not winnable ({} ms)", millis),
+        None => format!("Timed out after {} ms", millis),
+    }
+}
+
+pub fn describe_analysis(state: &GameState, results: &PlayResults) -> String {
+    let winning = results
+        .plays
+        .iter()
+        .filter(|(_, won)| *won)
+        .map(|(play, _)| play)
+        .collect::<Vec<_>>();
+    let mut lines = vec![format!(
+        "{} of {} first tricks keep the game winnable ({} positions searched)",
+        winning.len(),
+        results.plays.len(),
+        results.nodes
+    )];
+    for play in winning.iter().take(MAX_LISTED_PLAYS) {
+        lines.push(format!("  {}", trick_text(state, play)));
+    }
+    if winning.len() > MAX_LISTED_PLAYS {
+        lines.push(format!("  and {} more", winning.len() - MAX_LISTED_PLAYS));
+    }
+    lines.join("\n")
+}
+
+pub fn describe_validation(state: &GameState, issues: &[Issue]) -> String {
+    let tricks = state.hands().iter().map(|h| h.num_set()).max().unwrap_or(0);
+    let valid = !issues.iter().any(Issue::is_error);
+    let mut lines = vec![format!(
+        "{}: {} tricks left, {} tasks, P{} leads",
+        if valid { "Valid game" } else { "Invalid game" },
+        tricks,
+        state.tasks().to_tokens().len(),
+        state.curr_leader() + 1
+    )];
+    for issue in issues {
+        let kind = if issue.is_error() { "Error" } else { "Warning" };
+        lines.push(format!("{}: {}", kind, issue));
+    }
+    lines.join("\n")
+}
+
+pub fn describe_post_mortem(report: &PostMortem) -> String {
+    let mut lines = vec![];
+    if !report.winnable_at_start {
+        lines.push("The game could not be won from the start".to_string());
+    }
+    for step in &report.steps {
+        lines.push(format!(
+            "Trick {}: P{} plays {:?}{}",
+            step.trick,
+            step.player + 1,
+            step.card,
+            if step.winnable { "" } else { " (lost)" }
+        ));
+    }
+    if let Some(losing) = &report.losing_card {
+        lines.push(format!(
+            "The game was lost when P{} played {:?} in trick {}; {} would have kept it winnable",
+            losing.player + 1,
+            losing.card,
+            losing.trick,
+            cards(&losing.alternatives)
+        ));
+    }
+    lines.join("\n")
+}
+
+pub fn describe_verification(verification: &Verification) -> String {
+    let mut lines = vec![];
+    for trick in &verification.tricks {
+        let winner = trick
+            .winner
+            .map_or(String::new(), |w| format!(", won by P{}", w + 1));
+        let status = if trick.passed() { "ok" } else { "FAILED" };
+        lines.push(format!(
+            "Trick {} (P{} leads{}): {}",
+            trick.number,
+            trick.leader + 1,
+            winner,
+            status
+        ));
+        for (task, token) in &trick.completed {
+            lines.push(format!("  completes {}", task_text(task, *token)));
+        }
+        for problem in &trick.problems {
+            lines.push(format!("  {}", problem));
+        }
+    }
+    if verification.unchecked > 0 {
+        lines.push(format!(
+            "{} later tricks not checked",
+            verification.unchecked
+        ));
+    }
+    if !verification.complete {
+        lines.push("The record stops before the end of the game".to_string());
+    }
+    lines.push(format!(
+        "{}; the crew {}",
+        if verification.passed() {
+            "Record passed"
+        } else {
+            "Record failed"
+        },
+        if verification.won { "won" } else { "lost" }
+    ));
+    lines.join("\n")
+}
+
+pub fn describe_bench(report: &BenchReport) -> String {
+    let result = match report.winnable {
+        Some(true) => "winnable",
+        Some(false) => "not winnable",
+        None => "timed out",
+    };
+    format!(
+        "{} runs, {}, {} positions searched\n{}",
+        report.runs,
+        result,
+        report.nodes,
+        duration_text(&report.duration)
+    )
+}
+
+pub fn describe_estimate(report: &EstimateReport) -> String {
+    format!(
+        "{} of {} deals won, {} lost, {} timed out (win rate {:.1}%)\n{}",
+        report.won,
+        report.deals,
+        report.lost,
+        report.timed_out,
+        report.win_rate * 100.0,
+        duration_text(&report.duration)
+    )
+}
+
+pub fn describe_difficulty(difficulty: &Difficulty) -> String {
+    let mut lines = vec![format!("Difficulty {:.1} / 100", difficulty.score)];
+    if !difficulty.winnable {
+        lines.push("The game can't be won".to_string());
+        return lines.join("\n");
+    }
+    lines.push(format!(
+        "{} of {} first tricks keep the game winnable",
+        difficulty.winning_root_plays, difficulty.root_plays
+    ));
+    lines.push(match difficulty.forced_trick {
+        Some(trick) => format!(
+            "Only one play wins in trick {} of {}",
+            trick, difficulty.tricks
+        ),
+        None => "No trick has only one winning play".to_string(),
+    });
+    if let Some(win_rate) = difficulty.random_deal_win_rate {
+        lines.push(format!(
+            "The same tasks are won on {:.1}% of random deals",
+            win_rate * 100.0
+        ));
+    }
+    lines.join("\n")
+}
+
+pub fn describe_explanation(explanation: &Explanation) -> String {
+    let mut lines = vec!["The game is lost because of these tasks:".to_string()];
+    for (task, token) in &explanation.core {
+        lines.push(format!("  {}", task_text(task, *token)));
+    }
+    if !explanation.dropped_tasks.is_empty() {
+        lines.push("These tasks play no part:".to_string());
+        for (task, token) in &explanation.dropped_tasks {
+            lines.push(format!("  {}", task_text(task, *token)));
+        }
+    }
+    if !explanation.dropped_tokens.is_empty() {
+        lines.push("These orders play no part:".to_string());
+        for (task, token) in &explanation.dropped_tokens {
+            lines.push(format!("  {}", task_text(task, Some(*token))));
+        }
+    }
+    if !explanation.minimal {
+        lines.push("The solver timed out, so fewer tasks may be enough to lose".to_string());
+    }
+    lines.join("\n")
+}
+
+pub fn describe_hint(hint: &[(Card, bool)]) -> String {
+    let with = |safe: bool| {
+        let cards = hint
+            .iter()
+            .filter(|(_, s)| *s == safe)
+            .map(|(c, _)| *c)
+            .collect::<Vec<_>>();
+        if cards.is_empty() {
+            "none".to_string()
+        } else {
+            self::cards(&cards)
+        }
+    };
+    format!("Safe: {}\nUnsafe: {}", with(true), with(false))
+}
+
+pub fn describe_invariants(invariants: &Invariants) -> String {
+    let mut lines = vec![format!(
+        "In all {} {}winning lines:",
+        invariants.lines,
+        if invariants.exhaustive {
+            ""
+        } else {
+            "sampled "
+        }
+    )];
+    if invariants.findings.is_empty() {
+        lines.push("  nothing is always the same".to_string());
+    }
+    for finding in &invariants.findings {
+        lines.push(format!("  {}", finding.describe(invariants.exhaustive)));
+    }
+    lines.join("\n")
+}
+
+pub fn describe_robustness(report: &RobustnessReport) -> String {
+    report
+        .policies
+        .iter()
+        .map(|p| {
+            format!(
+                "{}: {} of {} games won ({:.1}%)",
+                p.policy.name(),
+                p.won,
+                p.games,
+                p.success_rate * 100.0
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn duration_text(d: &DurationSummary) -> String {
+    format!(
+        "min {} ms, median {} ms, max {} ms, mean {:.1} ms",
+        d.min, d.median, d.max, d.mean
+    )
+}
+
+fn cards(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|c| format!("{:?}", c))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) fn task_text(task: &Task, token: Option<Token>) -> String {
+    let token = match token {
+        Some(Token::Absolute(n)) => format!(" (#{})", n),
+        Some(Token::Relative(n)) => format!(" ({})", ">".repeat(n as usize)),
+        Some(Token::Last) => " (last)".to_string(),
+        None => String::new(),
+    };
+    format!("P{} wins {:?}{}", task.player() + 1, task.card(), token)
+}
+
+/// The trick's cards in play order, starting with the leader.
+fn trick_text(state: &GameState, play: &Play) -> String {
+    state
+        .cards_by_seat(play)
+        .iter()
+        .map(|(seat, card)| format!("P{} {:?}", seat + 1, card))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::dealt_state;
+    use crate::tasks::TasksObjective;
+
+    #[test]
+    fn test_describe_state() {
+        let state = GameState::new(
+            [
+                CardSet::from_cards(&[B1, B9]),
+                CardSet::from_cards(&[B2, Y2]),
+                CardSet::from_cards(&[Y3, Y7]),
+                CardSet::from_cards(&[B4, Y4]),
+            ],
+            TasksObjective::from_tokens(&[(Task::new(0, Y2), Some(Token::Absolute(1)))]).unwrap(),
+            1,
+        );
+        assert_eq!(
+            describe_state(&state),
+            "P1: B1 B9\nP2 (leads): B2 Y2\nP3: Y3 Y7\nP4: B4 Y4\nTask: P1 wins Y2 (#1)"
+        );
+        assert_eq!(
+            trick_text(&state, &Play::new(&[B2, B4, B9, Y3])),
+            "P2 B2, P3 Y3, P4 B4, P1 B9"
+        );
+    }
+
+    #[test]
+    fn test_describe_dealt_game() {
+        let state = dealt_state(0, 2);
+        let text = describe_state(&state);
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 6);
+        for line in &lines[..4] {
+            let (_, cards) = line.split_once(": ").unwrap();
+            assert_eq!(cards.split(' ').count(), 10);
+        }
+        assert_eq!(lines[4], "Task: P1 wins Y3");
+
+        let hint = [(B1, true), (Y2, false), (R4, true)];
+        assert_eq!(describe_hint(&hint), "Safe: B1 R4\nUnsafe: Y2");
+        assert_eq!(describe_hint(&hint[..1]), "Safe: B1\nUnsafe: none");
+    }
+}
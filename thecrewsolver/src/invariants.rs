@@ -0,0 +1,417 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::card::{suit, Card, CardSet};
+use crate::play::Play;
+use crate::player::PlayerIndex;
+use crate::rng::Rng;
+use crate::solver::{GameState, Search};
+use crate::tasks::Task;
+
+pub struct InvariantOptions {
+    /// Winning lines are listed one by one up to this many; past that they are sampled.
+    pub max_lines: usize,
+    /// How many random winning lines to play out when there are too many to list.
+    pub samples: usize,
+    pub seed: u64,
+    pub timeout: Duration,
+}
+
+/// The colour of a card, which findings give instead of the card itself so they don't reveal who
+/// holds it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Suit {
+    Blue,
+    Yellow,
+    Magenta,
+    Green,
+    Rocket,
+}
+
+impl Suit {
+    pub fn of(card: Card) -> Suit {
+        [
+            (suit::BLUE, Suit::Blue),
+            (suit::YELLOW, Suit::Yellow),
+            (suit::MAGENTA, Suit::Magenta),
+            (suit::GREEN, Suit::Green),
+        ]
+        .into_iter()
+        .find(|(cards, _)| cards.contains(card))
+        .map_or(Suit::Rocket, |(_, suit)| suit)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Suit::Blue => "blue",
+            Suit::Yellow => "yellow",
+            Suit::Magenta => "magenta",
+            Suit::Green => "green",
+            Suit::Rocket => "rocket",
+        }
+    }
+}
+
+/// A fact that holds in every winning line that was looked at. Findings can be shared with the
+/// whole crew: none of them says which player holds which card.
+#[derive(Debug, PartialEq)]
+pub enum Finding {
+    /// The task's card is always taken with a card of this suit.
+    TaskWonWithSuit { task: Task, suit: Suit },
+    /// The task is always completed between these tricks (inclusive).
+    TaskWonBetween {
+        task: Task,
+        earliest: u8,
+        latest: u8,
+    },
+    /// The player always leads the given trick.
+    Leads { player: PlayerIndex, trick: u8 },
+    /// The card is always played by the given trick, by whoever holds it.
+    PlayedBy { card: Card, trick: u8 },
+}
+
+impl Finding {
+    /// The finding as a rule when it holds in every winning line, or as what happened in each of
+    /// them when the lines were only a sample, since then a line that breaks it may have been
+    /// missed.
+    pub fn describe(&self, exhaustive: bool) -> String {
+        let (win, lead, played) = match exhaustive {
+            true => ("must win", "must lead", "must be played"),
+            false => ("won", "led", "was played"),
+        };
+        match self {
+            Finding::TaskWonWithSuit {
+                task,
+                suit: Suit::Rocket,
+            } => format!(
+                "P{} {} {:?} with a rocket",
+                task.player() + 1,
+                win,
+                task.card()
+            ),
+            Finding::TaskWonWithSuit { task, suit } => format!(
+                "P{} {} {:?} with a {} card",
+                task.player() + 1,
+                win,
+                task.card(),
+                suit.name()
+            ),
+            Finding::TaskWonBetween {
+                task,
+                earliest,
+                latest,
+            } if earliest == latest => format!(
+                "P{} {} {:?} in trick {}",
+                task.player() + 1,
+                win,
+                task.card(),
+                earliest
+            ),
+            Finding::TaskWonBetween {
+                task,
+                earliest,
+                latest,
+            } => format!(
+                "P{} {} {:?} between tricks {} and {}",
+                task.player() + 1,
+                win,
+                task.card(),
+                earliest,
+                latest
+            ),
+            Finding::Leads { player, trick } => {
+                format!("P{} {} trick {}", player + 1, lead, trick)
+            }
+            Finding::PlayedBy { card, trick } => {
+                format!("{:?} {} by trick {}", card, played, trick)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.describe(true))
+    }
+}
+
+pub struct Invariants {
+    /// Number of winning lines the findings are based on.
+    pub lines: usize,
+    /// Whether those were all the winning lines, rather than a sample.
+    pub exhaustive: bool,
+    pub findings: Vec<Finding>,
+}
+
+/// A trick of a winning line.
+#[derive(Clone)]
+struct Trick {
+    number: u8,
+    leader: PlayerIndex,
+    winning_card: Card,
+    /// Each card of the trick with the player who played it.
+    cards: Vec<(PlayerIndex, Card)>,
+}
+
+type Line = Vec<Trick>;
+
+/// Collects the winning lines of a deal and reports what they all have in common. Half of the
+/// timeout goes to listing every winning line; if there are too many, or listing takes too long,
+/// winning lines are sampled for the rest of the time instead. Returns `None` if the deal can't be
+/// won or no winning line was found in time.
+pub fn invariants(state: &GameState, options: &InvariantOptions) -> Option<Invariants> {
+    let start = Instant::now();
+    let mut search = Search::new(Some(start + options.timeout / 2));
+    let mut lines = vec![];
+    let listed = list_lines(
+        &mut search,
+        state,
+        &mut vec![],
+        &mut lines,
+        options.max_lines,
+    );
+
+    let exhaustive = listed == Some(true);
+    if !exhaustive {
+        let mut search = Search::new(Some(start + options.timeout));
+        let mut rng = Rng::new(options.seed);
+        lines = (0..options.samples)
+            .map_while(|_| sample_line(&mut search, state, &mut rng))
+            .flatten()
+            .collect();
+    }
+    if lines.is_empty() {
+        return None;
+    }
+
+    Some(Invariants {
+        lines: lines.len(),
+        exhaustive,
+        findings: findings(state, &lines),
+    })
+}
+
+/// Depth-first listing of every winning line. Returns `false` once more than `max_lines` are
+/// found.
+fn list_lines(
+    search: &mut Search,
+    state: &GameState,
+    prefix: &mut Line,
+    lines: &mut Vec<Line>,
+    max_lines: usize,
+) -> Option<bool> {
+    for play in state.plays() {
+        if !search.solve_play(state, &play)? {
+            continue;
+        }
+        let next = state.after_trick(&play)?;
+        prefix.push(trick(state, &play));
+        if next.is_won() {
+            lines.push(prefix.clone());
+            if lines.len() > max_lines {
+                return Some(false);
+            }
+        } else if !list_lines(search, &next, prefix, lines, max_lines)? {
+            return Some(false);
+        }
+        prefix.pop();
+    }
+    Some(true)
+}
+
+/// Plays out a game by trying the plays of each trick in random order and taking the first one
+/// that still wins, which picks uniformly among the winning plays. Returns `Some(None)` if the game
+/// can't be won.
+fn sample_line(search: &mut Search, state: &GameState, rng: &mut Rng) -> Option<Option<Line>> {
+    let mut state = state.clone();
+    let mut line = vec![];
+    while !state.is_won() {
+        let mut plays = state.plays().collect::<Vec<_>>();
+        rng.shuffle(&mut plays);
+
+        let mut winning = None;
+        for play in plays {
+            if search.solve_play(&state, &play)? {
+                winning = Some(play);
+                break;
+            }
+        }
+        let Some(play) = winning else {
+            return Some(None);
+        };
+        line.push(trick(&state, &play));
+        state = state.after_trick(&play)?;
+    }
+    Some(Some(line))
+}
+
+fn trick(state: &GameState, play: &Play) -> Trick {
+    Trick {
+        number: state.tasks().tricks_played() + 1,
+        leader: state.curr_leader(),
+        winning_card: play.get_trick_winner().cards()[0],
+        cards: state.cards_by_seat(play),
+    }
+}
+
+fn findings(state: &GameState, lines: &[Line]) -> Vec<Finding> {
+    let first_trick = state.tasks().tricks_played() + 1;
+    let last_trick =
+        first_trick - 1 + state.hands().iter().map(|h| h.num_set()).max().unwrap_or(0) as u8;
+
+    let mut findings = vec![];
+    for (task, _) in state.tasks().to_tokens() {
+        let tricks = lines
+            .iter()
+            .filter_map(|line| played_in(line, task.card()))
+            .collect::<Vec<_>>();
+        let Some(first) = tricks.first() else {
+            continue;
+        };
+
+        let suit = Suit::of(first.winning_card);
+        if tricks.iter().all(|t| Suit::of(t.winning_card) == suit) {
+            findings.push(Finding::TaskWonWithSuit { task, suit });
+        }
+
+        let earliest = tricks.iter().map(|t| t.number).min().unwrap();
+        let latest = tricks.iter().map(|t| t.number).max().unwrap();
+        if earliest > first_trick || latest < last_trick {
+            findings.push(Finding::TaskWonBetween {
+                task,
+                earliest,
+                latest,
+            });
+        }
+    }
+
+    for number in first_trick + 1..=last_trick {
+        let leaders = lines
+            .iter()
+            .map(|line| Some(line.iter().find(|t| t.number == number)?.leader))
+            .collect::<Option<Vec<_>>>();
+        if let Some(&[player, ref rest @ ..]) = leaders.as_deref() {
+            if rest.iter().all(|p| *p == player) {
+                findings.push(Finding::Leads {
+                    player,
+                    trick: number,
+                });
+            }
+        }
+    }
+
+    let held = state.hands().iter().fold(CardSet::EMPTY, |a, h| a | *h);
+    for card in held.cards() {
+        let tricks = lines
+            .iter()
+            .map(|line| played_in(line, card))
+            .collect::<Option<Vec<_>>>();
+        let Some(tricks) = tricks.filter(|t| !t.is_empty()) else {
+            continue;
+        };
+        let latest = tricks.iter().map(|t| t.number).max().unwrap();
+        if latest < last_trick {
+            findings.push(Finding::PlayedBy {
+                card,
+                trick: latest,
+            });
+        }
+    }
+
+    findings
+}
+
+/// The trick of the line in which `card` was played.
+fn played_in(line: &Line, card: Card) -> Option<&Trick> {
+    line.iter()
+        .find(|t| t.cards.iter().any(|(_, c)| *c == card))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::toy_state;
+
+    fn options(max_lines: usize) -> InvariantOptions {
+        InvariantOptions {
+            max_lines,
+            samples: 20,
+            seed: 0,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn test_invariants() {
+        let task = Task::new(0, Y2);
+        let result = invariants(&toy_state(task), &options(100)).unwrap();
+
+        assert!(result.exhaustive);
+        assert_eq!(result.lines, 2);
+        assert_eq!(
+            result.findings,
+            vec![
+                Finding::TaskWonWithSuit {
+                    task,
+                    suit: Suit::Blue
+                },
+                Finding::TaskWonBetween {
+                    task,
+                    earliest: 2,
+                    latest: 2
+                },
+                Finding::Leads {
+                    player: 0,
+                    trick: 2
+                },
+                Finding::PlayedBy { card: B2, trick: 1 },
+                Finding::PlayedBy { card: B4, trick: 1 },
+                Finding::PlayedBy { card: B9, trick: 1 },
+            ]
+        );
+        assert_eq!(
+            result.findings[0].to_string(),
+            "P1 must win Y2 with a blue card"
+        );
+        assert_eq!(
+            result.findings[3].to_string(),
+            "B2 must be played by trick 1"
+        );
+    }
+
+    #[test]
+    fn test_sampled_invariants() {
+        let task = Task::new(0, Y2);
+        let result = invariants(&toy_state(task), &options(1)).unwrap();
+
+        assert!(!result.exhaustive);
+        assert_eq!(result.lines, 20);
+        let finding = Finding::TaskWonWithSuit {
+            task,
+            suit: Suit::Blue,
+        };
+        assert!(result.findings.contains(&finding));
+        assert_eq!(finding.describe(false), "P1 won Y2 with a blue card");
+        assert_eq!(
+            Finding::Leads {
+                player: 0,
+                trick: 2
+            }
+            .describe(false),
+            "P1 led trick 2"
+        );
+    }
+
+    #[test]
+    fn test_suit() {
+        assert_eq!(Suit::of(B9), Suit::Blue);
+        assert_eq!(Suit::of(G1), Suit::Green);
+        assert_eq!(Suit::of(R4), Suit::Rocket);
+    }
+
+    #[test]
+    fn test_unwinnable() {
+        assert!(invariants(&toy_state(Task::new(1, Y7)), &options(100)).is_none());
+    }
+}
@@ -0,0 +1,373 @@
+use serde::Serialize;
+
+use crate::card::Card;
+use crate::cli::{card_to_str, cards_by_seat, task_to_dto, ErrorDTO, TaskDTO};
+use crate::consistency::Issue;
+use crate::explain::Explanation;
+use crate::invariants::{Finding, Invariants};
+use crate::player::PlayerIndex;
+use crate::post_mortem::PostMortem;
+use crate::solver::{GameState, PlayResults};
+use crate::tasks::{Task, Token};
+use crate::verify::{Problem, Verification};
+
+#[derive(Serialize)]
+struct ExplanationDTO {
+    core: Vec<TaskDTO>,
+    dropped_tasks: Vec<TaskDTO>,
+    dropped_tokens: Vec<TaskDTO>,
+    minimal: bool,
+}
+
+/// Writes an explanation with every task in the same form as the game input.
+pub fn format_explanation(explanation: &Explanation) -> String {
+    let tasks = |tasks: &[(Task, Option<Token>)]| {
+        tasks
+            .iter()
+            .map(|(t, token)| task_to_dto(t, *token))
+            .collect()
+    };
+    let explanation = ExplanationDTO {
+        core: tasks(&explanation.core),
+        dropped_tasks: tasks(&explanation.dropped_tasks),
+        dropped_tokens: explanation
+            .dropped_tokens
+            .iter()
+            .map(|(t, token)| task_to_dto(t, Some(*token)))
+            .collect(),
+        minimal: explanation.minimal,
+    };
+    serde_json::to_string(&explanation).unwrap()
+}
+
+#[derive(Serialize)]
+struct HintDTO {
+    safe: Vec<String>,
+    #[serde(rename = "unsafe")]
+    unsafe_cards: Vec<String>,
+}
+
+/// Writes which cards keep the game winnable, as `{"safe": [...], "unsafe": [...]}`.
+pub fn format_hint(cards: &[(Card, bool)]) -> String {
+    let with = |safe: bool| {
+        cards
+            .iter()
+            .filter(|(_, s)| *s == safe)
+            .map(|(c, _)| card_to_str(*c))
+            .collect()
+    };
+    let hint = HintDTO {
+        safe: with(true),
+        unsafe_cards: with(false),
+    };
+    serde_json::to_string(&hint).unwrap()
+}
+
+#[derive(Serialize)]
+struct StepDTO {
+    trick: u8,
+    player_num: PlayerIndex,
+    card: String,
+    winnable: bool,
+}
+
+#[derive(Serialize)]
+struct LosingCardDTO {
+    trick: u8,
+    player_num: PlayerIndex,
+    card: String,
+    alternatives: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct PostMortemDTO {
+    winnable_at_start: bool,
+    losing_card: Option<LosingCardDTO>,
+    steps: Vec<StepDTO>,
+}
+
+pub fn format_post_mortem(report: &PostMortem) -> String {
+    let report = PostMortemDTO {
+        winnable_at_start: report.winnable_at_start,
+        losing_card: report.losing_card.as_ref().map(|l| LosingCardDTO {
+            trick: l.trick,
+            player_num: l.player,
+            card: card_to_str(l.card),
+            alternatives: l.alternatives.iter().map(|c| card_to_str(*c)).collect(),
+        }),
+        steps: report
+            .steps
+            .iter()
+            .map(|s| StepDTO {
+                trick: s.trick,
+                player_num: s.player,
+                card: card_to_str(s.card),
+                winnable: s.winnable,
+            })
+            .collect(),
+    };
+    serde_json::to_string(&report).unwrap()
+}
+
+#[derive(Serialize)]
+struct FindingDTO {
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    player_num: Option<PlayerIndex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    card: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task_card: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suit: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    earliest: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latest: Option<u8>,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct InvariantsDTO {
+    lines: usize,
+    exhaustive: bool,
+    findings: Vec<FindingDTO>,
+}
+
+/// Writes each finding with its fields and a sentence describing it.
+pub fn format_invariants(invariants: &Invariants) -> String {
+    let invariants = InvariantsDTO {
+        lines: invariants.lines,
+        exhaustive: invariants.exhaustive,
+        findings: invariants
+            .findings
+            .iter()
+            .map(|f| finding_to_dto(f, invariants.exhaustive))
+            .collect(),
+    };
+    serde_json::to_string(&invariants).unwrap()
+}
+
+fn finding_to_dto(finding: &Finding, exhaustive: bool) -> FindingDTO {
+    let mut dto = FindingDTO {
+        kind: "",
+        player_num: None,
+        card: None,
+        task_card: None,
+        suit: None,
+        earliest: None,
+        latest: None,
+        text: finding.describe(exhaustive),
+    };
+    match finding {
+        Finding::TaskWonWithSuit { task, suit } => {
+            dto.kind = "task_won_with_suit";
+            dto.player_num = Some(task.player());
+            dto.task_card = Some(card_to_str(task.card()));
+            dto.suit = Some(suit.name());
+        }
+        Finding::TaskWonBetween {
+            task,
+            earliest,
+            latest,
+        } => {
+            dto.kind = "task_won_between";
+            dto.player_num = Some(task.player());
+            dto.task_card = Some(card_to_str(task.card()));
+            dto.earliest = Some(*earliest);
+            dto.latest = Some(*latest);
+        }
+        Finding::Leads { player, trick } => {
+            dto.kind = "leads";
+            dto.player_num = Some(*player);
+            dto.earliest = Some(*trick);
+            dto.latest = Some(*trick);
+        }
+        Finding::PlayedBy { card, trick } => {
+            dto.kind = "played_by";
+            dto.card = Some(card_to_str(*card));
+            dto.latest = Some(*trick);
+        }
+    }
+    dto
+}
+
+#[derive(Serialize)]
+struct PlayDTO {
+    /// The card played by each seat.
+    cards: Vec<String>,
+    winnable: bool,
+}
+
+#[derive(Serialize)]
+struct AnalysisDTO {
+    winnable: bool,
+    nodes: u64,
+    plays: Vec<PlayDTO>,
+}
+
+/// Writes whether each first trick keeps the game winnable.
+pub fn format_analysis(state: &GameState, results: &PlayResults) -> String {
+    let analysis = AnalysisDTO {
+        winnable: results.plays.iter().any(|(_, won)| *won),
+        nodes: results.nodes,
+        plays: results
+            .plays
+            .iter()
+            .map(|(play, winnable)| PlayDTO {
+                cards: cards_by_seat(state, play),
+                winnable: *winnable,
+            })
+            .collect(),
+    };
+    serde_json::to_string(&analysis).unwrap()
+}
+
+#[derive(Serialize)]
+struct ValidationDTO {
+    valid: bool,
+    tricks_left: u32,
+    tasks: usize,
+    errors: Vec<ErrorDTO>,
+    warnings: Vec<ErrorDTO>,
+}
+
+/// Writes a summary of a game that parsed, with the consistency issues found in it. The game is
+/// valid if none of them are errors.
+pub fn format_validation(state: &GameState, issues: &[Issue]) -> String {
+    let with = |error: bool| {
+        issues
+            .iter()
+            .filter(|i| i.is_error() == error)
+            .map(|i| ErrorDTO {
+                code: i.code().to_string(),
+                message: i.to_string(),
+            })
+            .collect()
+    };
+    let validation = ValidationDTO {
+        valid: !issues.iter().any(Issue::is_error),
+        tricks_left: state.hands().iter().map(|h| h.num_set()).max().unwrap_or(0),
+        tasks: state.tasks().to_tokens().len(),
+        errors: with(true),
+        warnings: with(false),
+    };
+    serde_json::to_string(&validation).unwrap()
+}
+
+#[derive(Serialize)]
+struct ProblemDTO {
+    kind: &'static str,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct TrickReportDTO {
+    trick: u8,
+    leader: PlayerIndex,
+    winner: Option<PlayerIndex>,
+    completed: Vec<TaskDTO>,
+    passed: bool,
+    problems: Vec<ProblemDTO>,
+}
+
+#[derive(Serialize)]
+struct VerificationDTO {
+    passed: bool,
+    won: bool,
+    complete: bool,
+    unchecked: usize,
+    tricks: Vec<TrickReportDTO>,
+}
+
+/// Writes a pass/fail report for every trick, each problem with a sentence describing it.
+pub fn format_verification(verification: &Verification) -> String {
+    let verification = VerificationDTO {
+        passed: verification.passed(),
+        won: verification.won,
+        complete: verification.complete,
+        unchecked: verification.unchecked,
+        tricks: verification
+            .tricks
+            .iter()
+            .map(|t| TrickReportDTO {
+                trick: t.number,
+                leader: t.leader,
+                winner: t.winner,
+                completed: t
+                    .completed
+                    .iter()
+                    .map(|(task, token)| task_to_dto(task, *token))
+                    .collect(),
+                passed: t.passed(),
+                problems: t
+                    .problems
+                    .iter()
+                    .map(|p| ProblemDTO {
+                        kind: match p {
+                            Problem::WrongLength { .. } => "wrong_length",
+                            Problem::NotHeld { .. } => "not_held",
+                            Problem::DidNotFollowSuit { .. } => "did_not_follow_suit",
+                            Problem::WrongWinner { .. } => "wrong_winner",
+                            Problem::TaskFailed { .. } => "task_failed",
+                        },
+                        text: p.to_string(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+    serde_json::to_string(&verification).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::invariants::Suit;
+
+    #[test]
+    fn test_format_invariants() {
+        let invariants = Invariants {
+            lines: 2,
+            exhaustive: true,
+            findings: vec![
+                Finding::TaskWonWithSuit {
+                    task: Task::new(2, M7),
+                    suit: Suit::Rocket,
+                },
+                Finding::PlayedBy { card: G1, trick: 3 },
+                Finding::Leads {
+                    player: 1,
+                    trick: 2,
+                },
+            ],
+        };
+        assert_eq!(
+            format_invariants(&invariants),
+            r#"{"lines":2,"exhaustive":true,"findings":[{"kind":"task_won_with_suit","player_num":2,"task_card":"M7","suit":"rocket","text":"P3 must win M7 with a rocket"},{"kind":"played_by","card":"G1","latest":3,"text":"G1 must be played by trick 3"},{"kind":"leads","player_num":1,"earliest":2,"latest":2,"text":"P2 must lead trick 2"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_format_hint() {
+        assert_eq!(
+            format_hint(&[(B1, false), (B9, true), (R1, true)]),
+            r#"{"safe":["B9","R1"],"unsafe":["B1"]}"#
+        );
+    }
+
+    #[test]
+    fn test_format_explanation() {
+        let explanation = Explanation {
+            core: vec![(Task::new(1, Y7), None)],
+            dropped_tasks: vec![(Task::new(0, B9), Some(Token::Absolute(1)))],
+            dropped_tokens: vec![(Task::new(1, Y7), Token::Last)],
+            minimal: true,
+        };
+        assert_eq!(
+            format_explanation(&explanation),
+            r#"{"core":[{"task_type":"anytime","order":0,"card":"Y7","player_num":1}],"dropped_tasks":[{"task_type":"absolute","order":1,"card":"B9","player_num":0}],"dropped_tokens":[{"task_type":"last","order":0,"card":"Y7","player_num":1}],"minimal":true}"#
+        );
+    }
+}
@@ -0,0 +1,36 @@
+pub mod appstate;
+pub mod args;
+pub mod batch;
+pub mod bench;
+pub mod card;
+pub mod certificate;
+pub mod cli;
+pub mod consistency;
+pub mod deal;
+pub mod difficulty;
+pub mod estimate;
+pub mod explain;
+pub mod export;
+#[cfg(test)]
+mod fixtures;
+pub mod hint;
+pub mod human;
+pub mod invariants;
+pub mod json;
+pub mod missions;
+pub mod notation;
+pub mod parallel;
+pub mod play;
+pub mod player;
+pub mod post_mortem;
+pub mod random_mission;
+pub mod record;
+pub mod repl;
+pub mod rng;
+pub mod robustness;
+pub mod schema;
+pub mod search_tree;
+pub mod solver;
+pub mod tasks;
+pub mod tui;
+pub mod verify;
@@ -1,30 +1,591 @@
-mod card;
-mod cli;
-mod play;
-mod player;
-mod solver;
-mod tasks;
-
-use crate::cli::{parse, RunOutput};
-use crate::solver::solve;
+use std::env;
+use std::fs;
 use std::io;
-use std::time::Instant;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
 
-fn main() {
-    let output = match run() {
-        Some((result, duration)) => RunOutput::new(true, result, duration),
-        None => RunOutput::new(false, false, 0),
+use thecrewsolver::args::Args;
+use thecrewsolver::batch::{solve_batch, BatchOptions};
+use thecrewsolver::bench::bench;
+use thecrewsolver::certificate::{
+    certify, check_certificate, Certificate, CertifyError, DEFAULT_MAX_POSITIONS,
+};
+use thecrewsolver::cli::{
+    format_input, format_state, parse, parse_deal, parse_position, parse_record, parse_replay,
+    parse_transcript, OutputFormat, ParseError, RunOutput,
+};
+use thecrewsolver::deal::{DealGenerator, TaskTemplate};
+use thecrewsolver::difficulty::rate;
+use thecrewsolver::estimate::{estimate, EstimateOptions, EstimateReport};
+use thecrewsolver::explain::explain;
+use thecrewsolver::export::{export, ExportOptions, Format};
+use thecrewsolver::hint::{safe_cards, solve_position};
+use thecrewsolver::human::{
+    describe_analysis, describe_bench, describe_deal, describe_difficulty, describe_estimate,
+    describe_explanation, describe_hint, describe_invariants, describe_position,
+    describe_post_mortem, describe_robustness, describe_solve, describe_state, describe_validation,
+    describe_verification,
+};
+use thecrewsolver::invariants::{invariants, InvariantOptions};
+use thecrewsolver::json::{
+    format_analysis, format_explanation, format_hint, format_invariants, format_post_mortem,
+    format_validation, format_verification,
+};
+use thecrewsolver::missions::get_mission;
+use thecrewsolver::notation::format_notation;
+use thecrewsolver::player::NUM_PLAYERS;
+use thecrewsolver::post_mortem::{post_mortem, ReplayError};
+use thecrewsolver::random_mission::{random_mission, MissionRequest};
+use thecrewsolver::repl::{run, Session};
+use thecrewsolver::robustness::{robustness, Policy};
+use thecrewsolver::schema::{input_schema, output_schema};
+use thecrewsolver::search_tree::{search_tree, TreeOptions};
+use thecrewsolver::solver::{
+    solve_each_play, solve_within, GameState, Search, DEFAULT_CACHE_LIMIT,
+};
+use thecrewsolver::tasks::MAX_TASKS;
+use thecrewsolver::tui;
+use thecrewsolver::verify::verify;
+
+/// Commands that take a game read it from `--input FILE`, or from stdin without it, as JSON or in
+/// the compact notation. `--format human` prints plain text instead of JSON, except for `tree`,
+/// `certify`, `export`, `convert`, `batch`, `repl` and `tui`, which write formats of their own.
+/// Without a command the game is solved, as the backend expects.
+fn main() -> ExitCode {
+    let result = Args::parse(env::args().skip(1)).and_then(|args| match args.command() {
+        None | Some("solve") => solve_input(&args),
+        Some("batch") => batch_input(&args),
+        Some("analyze") => analyze_input(&args),
+        Some("validate") => validate_input(&args),
+        Some("convert") => convert_input(&args),
+        Some("replay") | Some("post-mortem") => replay_input(&args),
+        Some("bench") => bench_input(&args),
+        Some("generate") => generate(&args),
+        Some("estimate") => estimate_mission(&args),
+        Some("rate") => rate_input(&args),
+        Some("random-mission") => generate_random_mission(&args),
+        Some("export") => export_records(&args),
+        Some("explain") => explain_input(&args),
+        Some("hint") => hint_input(&args),
+        Some("invariants") => invariants_input(&args),
+        Some("certify") => certify_input(&args),
+        Some("check-certificate") => check_certificate_input(&args),
+        Some("tree") => tree_input(&args),
+        Some("verify") => verify_input(&args),
+        Some("record") => record_input(&args),
+        Some("repl") => repl(&args),
+        Some("tui") => tui_input(&args),
+        Some("robustness") => robustness_input(&args),
+        Some("schema") => schema(&args),
+        Some(command) => Err(format!("unknown command `{}`", command)),
+    });
+
+    match result {
+        Ok(output) => {
+            // `batch` has already written its results as it went.
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `solve [--timeout MS] [--cache N] [--format human|json]`, with the game (and any
+/// `current_trick`) as input
+fn solve_input(args: &Args) -> Result<String, String> {
+    let format = output_format(args)?;
+    let position = match parse_position(&read_input(args)?) {
+        Ok(position) => position,
+        Err(error) if format == OutputFormat::Json => {
+            return Ok(RunOutput::invalid(&error).to_json())
+        }
+        Err(error) => return Err(error.to_string()),
+    };
+    let start = Instant::now();
+    let mut search = match args.get("timeout")? {
+        Some(millis) => Search::new(Some(start + Duration::from_millis(millis))),
+        None => Search::new(None),
+    }
+    .with_cache_limit(args.get_or("cache", DEFAULT_CACHE_LIMIT)?);
+    let result = match position.failed_trick {
+        Some(_) => Some(false),
+        None => solve_position(&mut search, &position.state, &position.current_trick),
+    };
+    let millis = start.elapsed().as_millis();
+
+    Ok(match format {
+        OutputFormat::Json => {
+            RunOutput::new(result.is_some(), result.unwrap_or(false), millis).to_json()
+        }
+        OutputFormat::Human => describe_solve(result, millis),
+    })
+}
+
+/// `batch [--threads N] [--timeout MS] [--cache N]`, with one game per line as input, each with an
+/// `id`. Writes one `solve` result per line, with the game's `id`, as soon as it is solved.
+fn batch_input(args: &Args) -> Result<String, String> {
+    reject_format(args)?;
+    let options = BatchOptions {
+        threads: args.get_or("threads", default_threads())?,
+        timeout: args.get("timeout")?.map(Duration::from_millis),
+        cache_limit: args.get_or("cache", DEFAULT_CACHE_LIMIT)?,
+    };
+    let stdout = &mut io::stdout();
+    match args.get::<PathBuf>("input")? {
+        Some(path) => {
+            let file = fs::File::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            solve_batch(io::BufReader::new(file), stdout, &options)
+        }
+        None => solve_batch(io::BufReader::new(io::stdin()), stdout, &options),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(String::new())
+}
+
+/// `analyze [--timeout MS] [--format human|json]`, with the game as input
+fn analyze_input(args: &Args) -> Result<String, String> {
+    let state = read_state(args)?;
+    let timeout = Duration::from_millis(args.get_or("timeout", 30000)?);
+    let results = solve_each_play(&state, timeout).ok_or("timed out")?;
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format_analysis(&state, &results),
+        OutputFormat::Human => describe_analysis(&state, &results),
+    })
+}
+
+/// `validate [--format human|json]`, with the game as input
+fn validate_input(args: &Args) -> Result<String, String> {
+    let (state, issues) = parse_deal(&read_input(args)?).map_err(|e| e.to_string())?;
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format_validation(&state, &issues),
+        OutputFormat::Human => describe_validation(&state, &issues),
+    })
+}
+
+/// `convert [--to notation|json]`, with the game as input in either form
+fn convert_input(args: &Args) -> Result<String, String> {
+    reject_format(args)?;
+    let (state, _) = parse_deal(&read_input(args)?).map_err(|e| e.to_string())?;
+    match args.get_or("to", "notation".to_string())?.as_ref() {
+        "notation" => Ok(format_notation(&state)),
+        "json" => Ok(format_state(&state, None)),
+        to => Err(format!("unknown format `{}`", to)),
+    }
+}
+
+/// `bench [--runs N] [--timeout MS] [--format human|json]`, with the game as input
+fn bench_input(args: &Args) -> Result<String, String> {
+    let state = read_state(args)?;
+    let report = bench(
+        &state,
+        args.get_or("runs", 5)?,
+        Duration::from_millis(args.get_or("timeout", 60000)?),
+    );
+    Ok(match output_format(args)? {
+        OutputFormat::Json => report.to_json(),
+        OutputFormat::Human => describe_bench(&report),
+    })
+}
+
+/// `generate --seed N [--players N] [--tasks N | --mission N] [--format human|json]`
+fn generate(args: &Args) -> Result<String, String> {
+    let seed = args.get_or("seed", 0)?;
+    let num_players = args.get_or("players", NUM_PLAYERS)?;
+    let mission = args.get("mission")?;
+    let template = task_template(args)?;
+
+    let mut generator = DealGenerator::new(seed);
+    let deal = generator
+        .deal(num_players)
+        .ok_or("players must be between 3 and 5")?;
+    let tasks = template.assign_tokens(&generator.draw_tasks(&deal, template.num_tasks));
+
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format_input(deal.hands(), &tasks, deal.commander(), mission),
+        OutputFormat::Human => describe_deal(deal.hands(), &tasks, deal.commander()),
+    })
+}
+
+/// `estimate (--mission N | --tasks N) [--deals N] [--seed N] [--timeout MS] [--threads N]
+/// [--format human|json]`
+fn estimate_mission(args: &Args) -> Result<String, String> {
+    let template = task_template(args)?;
+    let samples = estimate(&template, &estimate_options(args, 1000)?);
+    let report = EstimateReport::new(&samples);
+    Ok(match output_format(args)? {
+        OutputFormat::Json => report.to_json(),
+        OutputFormat::Human => describe_estimate(&report),
+    })
+}
+
+/// `rate [--deals N] [--seed N] [--timeout MS] [--threads N] [--format human|json]`, with the game
+/// as input
+fn rate_input(args: &Args) -> Result<String, String> {
+    let state = read_state(args)?;
+    let difficulty = rate(&state, &estimate_options(args, 200)?).ok_or("timed out")?;
+    Ok(match output_format(args)? {
+        OutputFormat::Json => difficulty.to_json(),
+        OutputFormat::Human => describe_difficulty(&difficulty),
+    })
+}
+
+/// `random-mission --seed N [--min-tasks N] [--max-tasks N] [--min-score X] [--max-score X]
+/// [--attempts N] [--deals N] [--timeout MS] [--threads N] [--format human|json]`
+fn generate_random_mission(args: &Args) -> Result<String, String> {
+    let request = MissionRequest {
+        min_tasks: num_tasks(args, "min-tasks", 1)?,
+        max_tasks: num_tasks(args, "max-tasks", 6)?,
+        min_score: args.get_or("min-score", 0.0)?,
+        max_score: args.get_or("max-score", 100.0)?,
+        max_attempts: args.get_or("attempts", 100)?,
+        rating: estimate_options(args, 50)?,
+    };
+    if request.min_tasks > request.max_tasks {
+        return Err("--min-tasks must be at most --max-tasks".to_string());
+    }
+    if request.min_score > request.max_score {
+        return Err("--min-score must be at most --max-score".to_string());
+    }
+
+    let mission = random_mission(args.get_or("seed", 0)?, &request)
+        .ok_or("no mission found in the requested difficulty band")?;
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format_state(&mission.state, None),
+        OutputFormat::Human => format!(
+            "{}\n{}\nFound after {} attempts",
+            describe_state(&mission.state),
+            describe_difficulty(&mission.difficulty),
+            mission.attempts
+        ),
+    })
+}
+
+/// `export (--mission N | --tasks N) --out FILE [--record-format jsonl|csv] [--records N]
+/// [--seed N] [--timeout MS] [--threads N]`
+fn export_records(args: &Args) -> Result<String, String> {
+    reject_format(args)?;
+    let path: PathBuf = args.get("out")?.ok_or("missing --out")?;
+    let template = task_template(args)?;
+    let options = ExportOptions {
+        records: args.get_or("records", 1000)?,
+        seed: args.get_or("seed", 0)?,
+        timeout: Duration::from_millis(args.get_or("timeout", 5000)?),
+        threads: args.get_or("threads", default_threads())?,
+        format: args.get_or("record-format", Format::Jsonl)?,
+        mission: args.get("mission")?,
+    };
+
+    let written = export(&path, &template, &options).map_err(|e| e.to_string())?;
+    Ok(format!("wrote {} records to {}", written, path.display()))
+}
+
+/// `explain [--timeout MS] [--format human|json]`, with an unwinnable game as input
+fn explain_input(args: &Args) -> Result<String, String> {
+    let state = read_state(args)?;
+    let timeout = Duration::from_millis(args.get_or("timeout", 5000)?);
+    match solve_within(&state, timeout) {
+        Some(true) => return Err("the game can be won".to_string()),
+        None => return Err("timed out".to_string()),
+        Some(false) => {}
+    }
+    let explanation = explain(&state, timeout).ok_or("timed out")?;
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format_explanation(&explanation),
+        OutputFormat::Human => describe_explanation(&explanation),
+    })
+}
+
+/// `hint --seat N [--timeout MS] [--format human|json]`, with the game (and any `current_trick`)
+/// as input
+fn hint_input(args: &Args) -> Result<String, String> {
+    let input = read_input(args)?;
+    let position = parse_position(&input).map_err(|e| e.to_string())?;
+    let seat = args.get("seat")?.ok_or("missing --seat")?;
+    let timeout = Duration::from_millis(args.get_or("timeout", 5000)?);
+
+    if let Some(trick) = position.failed_trick {
+        return Err(ParseError::GameLost(trick).to_string());
+    }
+    // `parse_position` has already checked that the trick could have been played.
+    let to_move = position.seat();
+    if seat != to_move {
+        return Err(format!(
+            "it is seat {}'s turn, not seat {}'s",
+            to_move, seat
+        ));
+    }
+    let cards =
+        safe_cards(&position.state, &position.current_trick, seat, timeout).ok_or("timed out")?;
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format_hint(&cards),
+        OutputFormat::Human => describe_hint(&cards),
+    })
+}
+
+/// `replay [--timeout MS] [--format human|json]` (also `post-mortem`), with the game and its
+/// recorded `tricks` as input
+fn replay_input(args: &Args) -> Result<String, String> {
+    let input = read_input(args)?;
+    let (state, tricks) = parse_replay(&input).map_err(|e| e.to_string())?;
+    let timeout = Duration::from_millis(args.get_or("timeout", 5000)?);
+
+    let report = post_mortem(&state, &tricks, timeout).map_err(|e| match e {
+        ReplayError::IllegalCard { trick, card } => {
+            format!("{:?} can't be played in trick {}", card, trick)
+        }
+        ReplayError::TrickTooLong { trick } => format!("trick {} has too many cards", trick),
+        ReplayError::IncompleteTrick { trick } => {
+            format!("trick {} is missing cards but isn't the last one", trick)
+        }
+        ReplayError::TimedOut => "timed out".to_string(),
+    })?;
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format_post_mortem(&report),
+        OutputFormat::Human => describe_post_mortem(&report),
+    })
+}
+
+/// `invariants [--max-lines N] [--samples N] [--seed N] [--timeout MS] [--format human|json]`,
+/// with a winnable game as input
+fn invariants_input(args: &Args) -> Result<String, String> {
+    let state = read_state(args)?;
+    let options = InvariantOptions {
+        max_lines: args.get_or("max-lines", 1000)?,
+        samples: args.get_or("samples", 200)?,
+        seed: args.get_or("seed", 0)?,
+        timeout: Duration::from_millis(args.get_or("timeout", 30000)?),
+    };
+    let result = invariants(&state, &options).ok_or("the game can't be won or timed out")?;
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format_invariants(&result),
+        OutputFormat::Human => describe_invariants(&result),
+    })
+}
+
+/// `certify [--timeout MS] [--max-positions N]`, with the game as input
+fn certify_input(args: &Args) -> Result<String, String> {
+    reject_format(args)?;
+    let state = read_state(args)?;
+    let timeout = Duration::from_millis(args.get_or("timeout", 30000)?);
+    let max_positions = args.get_or("max-positions", DEFAULT_MAX_POSITIONS)?;
+    let certificate = certify(&state, timeout, max_positions)
+        .map_err(|e| match e {
+            CertifyError::TimedOut => "timed out".to_string(),
+            CertifyError::TooManyPositions => format!(
+                "the proof needs more than {} positions; raise --max-positions",
+                max_positions
+            ),
+        })?
+        .ok_or("the game can be won")?;
+    Ok(certificate.to_json())
+}
+
+/// `check-certificate --certificate FILE [--format human|json]`, with the game as input
+fn check_certificate_input(args: &Args) -> Result<String, String> {
+    let state = read_state(args)?;
+    let path: PathBuf = args.get("certificate")?.ok_or("missing --certificate")?;
+    let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let certificate = Certificate::from_json(&json).ok_or("invalid certificate")?;
+    if !check_certificate(&state, &certificate) {
+        return Err("the certificate doesn't prove the game is lost".to_string());
+    }
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format!("{{\"valid\":true,\"nodes\":{}}}", certificate.nodes.len()),
+        OutputFormat::Human => format!(
+            "Valid certificate: {} positions prove the game is lost",
+            certificate.nodes.len()
+        ),
+    })
+}
+
+/// `tree [--depth N] [--max-nodes N] [--timeout MS]`, with the game as input; prints Graphviz DOT
+fn tree_input(args: &Args) -> Result<String, String> {
+    reject_format(args)?;
+    let state = read_state(args)?;
+    let options = TreeOptions {
+        max_depth: args.get_or("depth", 3)?,
+        max_nodes: args.get_or("max-nodes", 500)?,
+        timeout: Duration::from_millis(args.get_or("timeout", 30000)?),
     };
-    println!("{}", output.to_json())
+    let tree = search_tree(&state, &options).ok_or("timed out")?;
+    Ok(tree.to_dot())
 }
 
-fn run() -> Option<(bool, u128)> {
-    let input: String = io::read_to_string(io::stdin().lock()).ok()?;
-    let state = parse(&input)?;
+/// `verify [--format human|json]`, with the deal, every trick and optionally their `winners` as
+/// input
+fn verify_input(args: &Args) -> Result<String, String> {
+    let input = read_input(args)?;
+    let (state, tricks, winners) = parse_transcript(&input).map_err(|e| e.to_string())?;
+    let verification = verify(&state, &tricks, &winners);
+    Ok(match output_format(args)? {
+        OutputFormat::Json => format_verification(&verification),
+        OutputFormat::Human => describe_verification(&verification),
+    })
+}
+
+/// `record [--trick N [--cards N]] [--timeout MS] [--format human|json]`, with a game record as
+/// input. Solves from after the first N tricks and N cards of the next, or from the end of the
+/// record without `--trick`.
+fn record_input(args: &Args) -> Result<String, String> {
+    let record = parse_record(&read_input(args)?).map_err(|e| e.to_string())?;
+    let (tricks, cards) = match args.get("trick")? {
+        Some(tricks) => (tricks, args.get_or("cards", 0)?),
+        None => record.end(),
+    };
+    let position = record.position(tricks, cards).map_err(|e| e.to_string())?;
+    let timeout = Duration::from_millis(args.get_or("timeout", 60000)?);
 
     let start = Instant::now();
-    let result = solve(&state);
-    let duration = start.elapsed();
+    let result = position.solve(timeout);
+    let millis = start.elapsed().as_millis();
+    Ok(match output_format(args)? {
+        OutputFormat::Json => {
+            RunOutput::new(result.is_some(), result.unwrap_or(false), millis).to_json()
+        }
+        OutputFormat::Human => format!(
+            "{}\n{}",
+            describe_position(&position),
+            describe_solve(result, millis)
+        ),
+    })
+}
+
+/// `repl [--input FILE] [--timeout MS]`, which reads commands from stdin. `--input` loads a game
+/// before the first one.
+fn repl(args: &Args) -> Result<String, String> {
+    reject_format(args)?;
+    let mut session = Session::new(Duration::from_millis(args.get_or("timeout", 10000)?));
+    if let Some(path) = args.get::<String>("input")? {
+        println!("{}", session.execute(&format!("load {}", path))?);
+    }
+    println!("Type `help` for the commands");
+    run(&mut session, io::stdin().lock(), &mut io::stdout()).map_err(|e| e.to_string())?;
+    Ok(String::new())
+}
+
+/// `tui [--input FILE] [--seat N] [--timeout MS]`, the shell of `repl` drawn full screen. With
+/// `--seat` only that player's hand is shown.
+fn tui_input(args: &Args) -> Result<String, String> {
+    reject_format(args)?;
+    let seat = args.get::<u8>("seat")?;
+    if seat.is_some_and(|s| s as usize >= NUM_PLAYERS) {
+        return Err(format!("--seat must be below {}", NUM_PLAYERS));
+    }
+    let timeout = Duration::from_millis(args.get_or("timeout", 10000)?);
+    let mut session = Session::new(timeout);
+    if let Some(path) = args.get::<String>("input")? {
+        session.execute(&format!("load {}", path))?;
+    }
+    tui::run(
+        session,
+        io::BufReader::new(io::stdin()),
+        &mut io::stdout(),
+        seat,
+        timeout,
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(String::new())
+}
+
+/// `schema --kind input|output`, the JSON Schema of games or of solver results
+fn schema(args: &Args) -> Result<String, String> {
+    reject_format(args)?;
+    let schema = match args.get::<String>("kind")?.as_deref() {
+        Some("input") => input_schema(),
+        Some("output") => output_schema(),
+        Some(kind) => return Err(format!("unknown schema `{}`", kind)),
+        None => return Err("missing --kind (input or output)".to_string()),
+    };
+    Ok(serde_json::to_string_pretty(&schema).unwrap())
+}
+
+/// `robustness [--games N] [--seed N] [--policy random|greedy-to-task|lowest-card]
+/// [--format human|json]`, with the game as input
+fn robustness_input(args: &Args) -> Result<String, String> {
+    let state = read_state(args)?;
+    let policies = match args.get::<Policy>("policy")? {
+        Some(policy) => vec![policy],
+        None => Policy::ALL.to_vec(),
+    };
+    let report = robustness(
+        &state,
+        &policies,
+        args.get_or("games", 1000)?,
+        args.get_or("seed", 0)?,
+    );
+    Ok(match output_format(args)? {
+        OutputFormat::Json => report.to_json(),
+        OutputFormat::Human => describe_robustness(&report),
+    })
+}
+
+fn estimate_options(args: &Args, default_deals: usize) -> Result<EstimateOptions, String> {
+    Ok(EstimateOptions {
+        deals: args.get_or("deals", default_deals)?,
+        seed: args.get_or("seed", 0)?,
+        timeout: Duration::from_millis(args.get_or("timeout", 5000)?),
+        threads: args.get_or("threads", default_threads())?,
+    })
+}
+
+/// The contents of `--input FILE`, or stdin if it isn't given.
+fn read_input(args: &Args) -> Result<String, String> {
+    match args.get::<PathBuf>("input")? {
+        Some(path) => fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e)),
+        None => io::read_to_string(io::stdin().lock()).map_err(|e| e.to_string()),
+    }
+}
+
+fn read_state(args: &Args) -> Result<GameState, String> {
+    parse(&read_input(args)?).map_err(|e| e.to_string())
+}
+
+/// `--format human|json`, JSON by default.
+fn output_format(args: &Args) -> Result<OutputFormat, String> {
+    args.get_or("format", OutputFormat::Json)
+}
+
+/// For commands whose output has a fixed format of its own.
+fn reject_format(args: &Args) -> Result<(), String> {
+    if args.has("format") {
+        return Err("this command doesn't take --format".to_string());
+    }
+    Ok(())
+}
+
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// `--mission N` or `--tasks N`
+fn task_template(args: &Args) -> Result<TaskTemplate, String> {
+    match args.get("mission")? {
+        Some(number) => {
+            let mission = get_mission(number).ok_or("unknown mission")?;
+            if !mission.is_supported() {
+                return Err(format!(
+                    "mission {} has a rule the solver doesn't follow: {:?}",
+                    number,
+                    mission.rules()
+                ));
+            }
+            Ok(TaskTemplate::from_mission(mission))
+        }
+        None => Ok(TaskTemplate::new(num_tasks(args, "tasks", 0)?, &[])),
+    }
+}
 
-    Some((result, duration.as_millis()))
+fn num_tasks(args: &Args, name: &str, default: usize) -> Result<usize, String> {
+    let tasks = args.get_or(name, default)?;
+    if tasks > MAX_TASKS {
+        return Err(format!("--{} must be at most {}", name, MAX_TASKS));
+    }
+    Ok(tasks)
 }
@@ -0,0 +1,271 @@
+use crate::deal::TaskTemplate;
+use crate::tasks::{Task, TasksObjective, Token};
+
+/// Special rules from the logbook that change how a mission is played. Only the task tokens are
+/// enforced by the solver; the rest are carried along so callers can present them. Rules about
+/// communication don't matter to a solver that sees every hand, but the others do, see
+/// `Rule::affects_play`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rule {
+    /// Communication tokens may only be placed face down.
+    DeadZone,
+    /// Nobody may communicate before the given trick.
+    Disruption(u8),
+    /// The commander picks one crew member who may not communicate.
+    SilentCrewMember,
+    /// The commander hands out the tasks instead of the crew choosing them.
+    CommanderDistributes,
+    /// A crew member chosen by the commander must not win any trick.
+    SickCrewMember,
+    /// At least one trick must be won with a 1.
+    OneWinsTrick,
+    /// Every rocket must win a trick.
+    EachRocketWinsTrick,
+    /// No 9 may win a trick.
+    NoNineWinsTrick,
+    /// After the first trick every crew member passes a card to their neighbour.
+    PassCard,
+    /// The commander must win the first and the last trick.
+    CommanderWinsFirstAndLast,
+}
+
+impl Rule {
+    /// Whether the rule changes which tricks may be won or which cards are held. The solver
+    /// ignores these, so its results don't hold for missions that have them.
+    pub fn affects_play(&self) -> bool {
+        match self {
+            DeadZone | Disruption(_) | SilentCrewMember | CommanderDistributes => false,
+            SickCrewMember
+            | OneWinsTrick
+            | EachRocketWinsTrick
+            | NoNineWinsTrick
+            | PassCard
+            | CommanderWinsFirstAndLast => true,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Mission {
+    number: u8,
+    num_tasks: u8,
+    tokens: &'static [Token],
+    rules: &'static [Rule],
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MissionSetup {
+    pub tasks: TasksObjective,
+    pub rules: &'static [Rule],
+}
+
+impl Mission {
+    const fn new(
+        number: u8,
+        num_tasks: u8,
+        tokens: &'static [Token],
+        rules: &'static [Rule],
+    ) -> Self {
+        Self {
+            number,
+            num_tasks,
+            tokens,
+            rules,
+        }
+    }
+
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    pub fn num_tasks(&self) -> u8 {
+        self.num_tasks
+    }
+
+    pub fn tokens(&self) -> &'static [Token] {
+        self.tokens
+    }
+
+    pub fn rules(&self) -> &'static [Rule] {
+        self.rules
+    }
+
+    /// Whether the solver can play the mission, which it can't if a rule affects play.
+    pub fn is_supported(&self) -> bool {
+        !self.rules.iter().any(Rule::affects_play)
+    }
+
+    /// Expands the mission for tasks given in the order they were drawn. Tokens are handed out to
+    /// the first tasks, the remaining tasks can be completed at any time.
+    pub fn setup(&self, tasks: &[Task]) -> Option<MissionSetup> {
+        (tasks.len() == self.num_tasks as usize).then_some(())?;
+
+        Some(MissionSetup {
+            tasks: TasksObjective::from_tokens(
+                &TaskTemplate::from_mission(self).assign_tokens(tasks),
+            )?,
+            rules: self.rules,
+        })
+    }
+
+    /// Checks that a task set, given as the token on each task, is the one this mission asks for.
+    pub fn matches(&self, tokens: &[Option<Token>]) -> bool {
+        let mut expected = self.tokens.to_vec();
+        let mut actual = tokens.iter().flatten().copied().collect::<Vec<_>>();
+        expected.sort_by_key(token_sort_key);
+        actual.sort_by_key(token_sort_key);
+
+        tokens.len() == self.num_tasks as usize && expected == actual
+    }
+}
+
+fn token_sort_key(token: &Token) -> (u8, u8) {
+    match token {
+        Token::Absolute(order) => (0, *order),
+        Token::Relative(order) => (1, *order),
+        Token::Last => (2, 0),
+    }
+}
+
+pub fn get_mission(number: u8) -> Option<&'static Mission> {
+    MISSIONS.get((number as usize).checked_sub(1)?)
+}
+
+use Rule::*;
+use Token::{Absolute as A, Last as L, Relative as R};
+
+/// The 50 missions of the base game logbook.
+pub const MISSIONS: [Mission; 50] = [
+    Mission::new(1, 1, &[], &[]),
+    Mission::new(2, 2, &[], &[]),
+    Mission::new(3, 2, &[A(1), A(2)], &[]),
+    Mission::new(4, 3, &[A(1)], &[]),
+    Mission::new(5, 0, &[], &[SickCrewMember]),
+    Mission::new(6, 3, &[R(1), R(2)], &[]),
+    Mission::new(7, 3, &[L], &[]),
+    Mission::new(8, 3, &[A(1), A(2), A(3)], &[]),
+    Mission::new(9, 0, &[], &[OneWinsTrick]),
+    Mission::new(10, 4, &[], &[]),
+    Mission::new(11, 4, &[A(1)], &[SilentCrewMember]),
+    Mission::new(12, 4, &[L], &[PassCard]),
+    Mission::new(13, 0, &[], &[EachRocketWinsTrick]),
+    Mission::new(14, 4, &[R(1), R(2), R(3)], &[DeadZone]),
+    Mission::new(15, 4, &[A(1), A(2), A(3), A(4)], &[]),
+    Mission::new(16, 0, &[], &[NoNineWinsTrick]),
+    Mission::new(17, 2, &[], &[DeadZone, NoNineWinsTrick]),
+    Mission::new(18, 5, &[A(1), A(2)], &[Disruption(2)]),
+    Mission::new(19, 5, &[A(1)], &[SilentCrewMember]),
+    Mission::new(20, 2, &[L], &[SickCrewMember]),
+    Mission::new(21, 2, &[A(1), A(2)], &[CommanderDistributes]),
+    Mission::new(22, 0, &[], &[CommanderWinsFirstAndLast]),
+    Mission::new(23, 5, &[A(1), A(2), A(3), A(4), A(5)], &[]),
+    Mission::new(24, 6, &[R(1), R(2), R(3)], &[DeadZone]),
+    Mission::new(25, 6, &[A(1), A(2), L], &[Disruption(3)]),
+    Mission::new(26, 0, &[], &[OneWinsTrick, DeadZone]),
+    Mission::new(27, 3, &[], &[CommanderDistributes, Disruption(4)]),
+    Mission::new(28, 6, &[A(1), L], &[SilentCrewMember]),
+    Mission::new(29, 0, &[], &[EachRocketWinsTrick, DeadZone]),
+    Mission::new(30, 6, &[R(1), R(2)], &[PassCard]),
+    Mission::new(31, 6, &[A(1), A(2), A(3)], &[DeadZone]),
+    Mission::new(32, 7, &[R(1), R(2), R(3), R(4)], &[]),
+    Mission::new(33, 0, &[], &[SickCrewMember, DeadZone]),
+    Mission::new(34, 0, &[], &[CommanderWinsFirstAndLast, DeadZone]),
+    Mission::new(35, 7, &[A(1), A(2), A(3)], &[Disruption(3)]),
+    Mission::new(36, 7, &[A(1), A(2)], &[CommanderDistributes]),
+    Mission::new(37, 0, &[], &[OneWinsTrick, Disruption(3)]),
+    Mission::new(38, 8, &[], &[SilentCrewMember]),
+    Mission::new(39, 8, &[R(1), R(2), R(3)], &[DeadZone]),
+    Mission::new(40, 8, &[A(1), A(2), A(3), L], &[PassCard]),
+    Mission::new(41, 0, &[], &[EachRocketWinsTrick, Disruption(4)]),
+    Mission::new(42, 9, &[], &[DeadZone]),
+    Mission::new(43, 9, &[A(1), A(2), L], &[CommanderDistributes]),
+    Mission::new(44, 0, &[], &[NoNineWinsTrick, SilentCrewMember]),
+    Mission::new(45, 9, &[R(1), R(2), R(3), R(4)], &[Disruption(5)]),
+    Mission::new(46, 0, &[], &[SickCrewMember, Disruption(2)]),
+    Mission::new(47, 10, &[], &[DeadZone]),
+    Mission::new(48, 3, &[L], &[CommanderWinsFirstAndLast]),
+    Mission::new(49, 10, &[A(1), A(2), A(3)], &[DeadZone]),
+    Mission::new(
+        50,
+        0,
+        &[],
+        &[CommanderWinsFirstAndLast, EachRocketWinsTrick],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+
+    #[test]
+    fn test_get_mission() {
+        assert_eq!(get_mission(0), None);
+        assert_eq!(get_mission(1).map(|m| m.number()), Some(1));
+        assert_eq!(get_mission(50).map(|m| m.number()), Some(50));
+        assert_eq!(get_mission(51), None);
+    }
+
+    #[test]
+    fn test_missions_are_numbered_in_order() {
+        for (i, mission) in MISSIONS.iter().enumerate() {
+            assert_eq!(mission.number() as usize, i + 1);
+            assert!(mission.tokens().len() <= mission.num_tasks() as usize);
+        }
+    }
+
+    #[test]
+    fn test_is_supported() {
+        assert!(get_mission(8).unwrap().is_supported());
+        assert!(get_mission(14).unwrap().is_supported());
+        assert!(!get_mission(12).unwrap().is_supported());
+        assert!(!get_mission(17).unwrap().is_supported());
+        // Every mission without tasks is played for a rule alone.
+        for mission in MISSIONS.iter().filter(|m| m.num_tasks() == 0) {
+            assert!(!mission.is_supported());
+        }
+    }
+
+    #[test]
+    fn test_setup() {
+        let mission = get_mission(3).unwrap();
+        assert_eq!(
+            mission.setup(&[Task::new(0, B1), Task::new(1, G3)]),
+            Some(MissionSetup {
+                tasks: TasksObjective::new(&[Task::new(0, B1), Task::new(1, G3)], &[], &[], None),
+                rules: &[],
+            })
+        );
+        assert_eq!(mission.setup(&[Task::new(0, B1)]), None);
+
+        let mission = get_mission(7).unwrap();
+        assert_eq!(
+            mission.setup(&[Task::new(0, B1), Task::new(1, G3), Task::new(2, Y5)]),
+            Some(MissionSetup {
+                tasks: TasksObjective::new(
+                    &[],
+                    &[],
+                    &[Task::new(1, G3), Task::new(2, Y5)],
+                    Some(Task::new(0, B1))
+                ),
+                rules: &[],
+            })
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let mission = get_mission(8).unwrap();
+        assert!(mission.matches(&[
+            Some(Token::Absolute(2)),
+            Some(Token::Absolute(1)),
+            Some(Token::Absolute(3))
+        ]));
+        assert!(!mission.matches(&[Some(Token::Absolute(1)), Some(Token::Absolute(2)), None]));
+        assert!(!mission.matches(&[Some(Token::Absolute(1)), Some(Token::Absolute(2))]));
+
+        let mission = get_mission(10).unwrap();
+        assert!(mission.matches(&[None, None, None, None]));
+        assert!(!mission.matches(&[None, None, None, Some(Token::Last)]));
+    }
+}
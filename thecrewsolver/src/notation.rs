@@ -0,0 +1,313 @@
+use crate::cli::{ParseError, State, TaskDTO, SCHEMA_VERSION};
+use crate::player::{PlayerIndex, NUM_PLAYERS};
+use crate::solver::GameState;
+use crate::tasks::{Task, Token};
+
+/// Marks the task that must be won in the last trick. `last` is read as well, for keyboards
+/// without it.
+const LAST: &str = "Ω";
+
+/// Writes a game in the compact notation that `cli::parse` reads as well as JSON, for example
+/// `P1: B1 B4 Y9 / P2: ... / tasks: 1>P2:G3, >>P1:M7, *P4:B2, Ω P3:R1 / lead P1`. Sections are
+/// separated by `/` and players are counted from 1. A task is marked `N>` for absolute order N,
+/// with one `>` per step of relative order, `Ω` for the last trick or `*` for any time, and
+/// `@2..5` after the card limits it to those tricks. `played N` follows once tricks have been
+/// played.
+pub fn format_notation(state: &GameState) -> String {
+    let mut sections = state
+        .hands()
+        .iter()
+        .enumerate()
+        .map(|(player, hand)| {
+            let cards = hand
+                .cards()
+                .iter()
+                .map(|c| format!(" {:?}", c))
+                .collect::<String>();
+            format!("P{}:{}", player + 1, cards)
+        })
+        .collect::<Vec<_>>();
+    let tasks = state
+        .tasks()
+        .to_tokens()
+        .iter()
+        .map(|(task, token)| task_text(task, *token))
+        .collect::<Vec<_>>();
+    sections.push(match tasks.is_empty() {
+        true => "tasks:".to_string(),
+        false => format!("tasks: {}", tasks.join(", ")),
+    });
+    sections.push(format!("lead P{}", state.curr_leader() + 1));
+    let tricks_played = state.tasks().tricks_played();
+    if tricks_played > 0 {
+        sections.push(format!("played {}", tricks_played));
+    }
+    sections.join(" / ")
+}
+
+fn task_text(task: &Task, token: Option<Token>) -> String {
+    let marker = match token {
+        Some(Token::Absolute(order)) => format!("{}>", order),
+        Some(Token::Relative(order)) => ">".repeat(order as usize),
+        Some(Token::Last) => format!("{} ", LAST),
+        None => "*".to_string(),
+    };
+    let window = match (task.earliest(), task.latest()) {
+        (None, None) => String::new(),
+        (earliest, latest) => format!(
+            "@{}..{}",
+            earliest.map_or(String::new(), |e| e.to_string()),
+            latest.map_or(String::new(), |l| l.to_string())
+        ),
+    };
+    format!(
+        "{}P{}:{:?}{}",
+        marker,
+        task.player() + 1,
+        task.card(),
+        window
+    )
+}
+
+/// Input in the notation starts with a section name or a player, where JSON starts with `{`.
+pub(crate) fn is_notation(input: &str) -> bool {
+    input
+        .trim_start()
+        .starts_with(|c: char| c.is_ascii_alphabetic())
+}
+
+/// Reads the notation into the same form as JSON input, so it goes through the same checks. A
+/// `mission N` section hands out the mission's tokens to tasks without a marker, as JSON tasks
+/// without a type do; otherwise unmarked tasks can be done at any time.
+pub(crate) fn read_notation(input: &str) -> Result<State, ParseError> {
+    let mut hands: Vec<Option<Vec<String>>> = vec![None; NUM_PLAYERS];
+    let mut tasks = None;
+    let mut curr_leader = None;
+    let mut tricks_played = None;
+    let mut mission = None;
+
+    for section in input.split('/').map(str::trim) {
+        if let Some(rest) = section.strip_prefix("tasks:") {
+            let list = rest
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(read_task)
+                .collect::<Result<Vec<_>, _>>()?;
+            set_once(&mut tasks, list, "tasks")?;
+        } else if let Some(rest) = section.strip_prefix("lead ") {
+            set_once(&mut curr_leader, read_player(rest.trim())?, "lead")?;
+        } else if let Some(rest) = section.strip_prefix("played ") {
+            set_once(&mut tricks_played, read_number(rest)?, "played")?;
+        } else if let Some(rest) = section.strip_prefix("mission ") {
+            set_once(&mut mission, read_number(rest)?, "mission")?;
+        } else if let Some((player, cards)) = section.split_once(':') {
+            let player = read_player(player.trim())?;
+            let cards = cards.split_whitespace().map(String::from).collect();
+            set_once(
+                &mut hands[player as usize],
+                cards,
+                &format!("P{}", player + 1),
+            )?;
+        } else {
+            return Err(invalid(format!("unknown section `{}`", section)));
+        }
+    }
+
+    let hands = hands
+        .into_iter()
+        .enumerate()
+        .map(|(player, hand)| hand.ok_or_else(|| invalid(format!("no hand for P{}", player + 1))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut tasks = tasks.unwrap_or_default();
+    if mission.is_none() {
+        for task in tasks.iter_mut().filter(|t| t.task_type.is_empty()) {
+            task.task_type = "anytime".to_string();
+        }
+    }
+    Ok(State {
+        version: SCHEMA_VERSION,
+        hands,
+        tasks,
+        curr_leader: curr_leader.ok_or_else(|| invalid("no `lead` section".to_string()))?,
+        tricks_played: tricks_played.unwrap_or(0),
+        mission,
+        current_trick: vec![],
+        tricks: vec![],
+        winners: vec![],
+    })
+}
+
+/// `[marker]P<player>:<card>[@earliest..latest]`
+fn read_task(text: &str) -> Result<TaskDTO, ParseError> {
+    let (task_type, order, rest) = read_marker(text)?;
+    let (player, card) = rest.trim().split_once(':').ok_or_else(|| {
+        invalid(format!(
+            "task `{}` has no `:` between player and card",
+            text
+        ))
+    })?;
+    let (card, window) = match card.split_once('@') {
+        Some((card, window)) => (card, Some(window)),
+        None => (card, None),
+    };
+    let (earliest, latest) = match window {
+        Some(window) => {
+            let (earliest, latest) = window.split_once("..").ok_or_else(|| {
+                invalid(format!("trick window `{}` should look like `2..5`", window))
+            })?;
+            let bound = |b: &str| match b.is_empty() {
+                true => Ok(None),
+                false => read_number(b).map(Some),
+            };
+            (bound(earliest)?, bound(latest)?)
+        }
+        None => (None, None),
+    };
+    Ok(TaskDTO {
+        task_type: task_type.to_string(),
+        order,
+        card: card.trim().to_string(),
+        player_num: read_player(player.trim())?,
+        earliest,
+        latest,
+    })
+}
+
+/// The task's type and order, and the text after its marker.
+fn read_marker(text: &str) -> Result<(&'static str, u8, &str), ParseError> {
+    if let Some(rest) = text
+        .strip_prefix(LAST)
+        .or_else(|| text.strip_prefix("last"))
+    {
+        return Ok(("last", 0, rest));
+    }
+    if let Some(rest) = text.strip_prefix('*') {
+        return Ok(("anytime", 0, rest));
+    }
+    if text.starts_with('>') {
+        let rest = text.trim_start_matches('>');
+        return Ok(("relative", (text.len() - rest.len()) as u8, rest));
+    }
+    match text.split_once('>') {
+        Some((order, rest)) if !order.contains(':') => Ok(("absolute", read_number(order)?, rest)),
+        _ => Ok(("", 0, text)),
+    }
+}
+
+fn read_player(text: &str) -> Result<PlayerIndex, ParseError> {
+    text.strip_prefix('P')
+        .and_then(|n| n.parse::<usize>().ok())
+        .filter(|n| (1..=NUM_PLAYERS).contains(n))
+        .map(|n| (n - 1) as PlayerIndex)
+        .ok_or_else(|| {
+            invalid(format!(
+                "`{}` isn't a player; players are P1 to P{}",
+                text, NUM_PLAYERS
+            ))
+        })
+}
+
+fn read_number(text: &str) -> Result<u8, ParseError> {
+    text.trim()
+        .parse()
+        .map_err(|_| invalid(format!("`{}` isn't a number", text.trim())))
+}
+
+fn set_once<T>(slot: &mut Option<T>, value: T, section: &str) -> Result<(), ParseError> {
+    match slot.replace(value) {
+        Some(_) => Err(invalid(format!("`{}` is given more than once", section))),
+        None => Ok(()),
+    }
+}
+
+fn invalid(message: String) -> ParseError {
+    ParseError::Notation(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Card::*, CardSet};
+    use crate::cli::parse;
+    use crate::fixtures::{dealt_state, toy_state};
+    use crate::tasks::TasksObjective;
+
+    #[test]
+    fn test_round_trip() {
+        let text = "P1: B1 B9 / P2: B2 Y2 / P3: Y3 Y7 / P4: B4 Y4 / tasks: *P1:Y2 / lead P1";
+        let state = toy_state(Task::new(0, Y2));
+        assert_eq!(format_notation(&state), text);
+        assert_eq!(parse(text), Ok(state));
+
+        let state = dealt_state(3, 6);
+        assert_eq!(parse(&format_notation(&state)), Ok(state));
+    }
+
+    #[test]
+    fn test_tokens() {
+        let hands = [&[B1, M7][..], &[G3, Y9], &[R1, Y1], &[B2, G1]];
+        let tasks = TasksObjective::from_tokens(&[
+            (Task::new(1, G3), Some(Token::Absolute(1))),
+            (Task::new(0, M7), Some(Token::Relative(2))),
+            (Task::new(0, B1), Some(Token::Relative(1))),
+            (Task::new(3, B2).with_window(Some(2), None), None),
+            (Task::new(2, R1), Some(Token::Last)),
+        ])
+        .unwrap()
+        .with_tricks_played(8);
+        let state = GameState::new(hands.map(CardSet::from_cards), tasks, 2);
+
+        let text = format_notation(&state);
+        assert_eq!(
+            text,
+            "P1: B1 M7 / P2: Y9 G3 / P3: Y1 R1 / P4: B2 G1 \
+             / tasks: 1>P2:G3, >P1:B1, >>P1:M7, *P4:B2@2.., Ω P3:R1 / lead P3 / played 8"
+        );
+        assert_eq!(parse(&text), Ok(state.clone()));
+
+        let loose = "lead P3 / played 8 / P4: G1 B2 / P3:R1 Y1 / P2: G3 Y9 / P1: M7 B1 \
+                     / tasks: last P3:R1, >>P1:M7, >P1:B1, P4:B2@2.., 1>P2:G3";
+        assert_eq!(parse(loose), Ok(state));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| parse(text).unwrap_err().to_string();
+        let hands = "P1: B1 / P2: B2 / P3: B3 / P4: B4";
+        assert_eq!(
+            error(&format!("{} / tasks: *P5:B1 / lead P1", hands)),
+            "invalid notation: `P5` isn't a player; players are P1 to P4"
+        );
+        assert_eq!(
+            error(&format!("{} / tasks: *P1-B1 / lead P1", hands)),
+            "invalid notation: task `*P1-B1` has no `:` between player and card"
+        );
+        assert_eq!(
+            error(&format!("{} / tasks: *P1:B1", hands)),
+            "invalid notation: no `lead` section"
+        );
+        assert_eq!(
+            error("P1: B1 / P2: B2 / P3: B3 / lead P1"),
+            "invalid notation: no hand for P4"
+        );
+        assert_eq!(
+            error(&format!("{} / P1: B5 / lead P1", hands)),
+            "invalid notation: `P1` is given more than once"
+        );
+        assert_eq!(
+            error(&format!("{} / lead P1 / trump R", hands)),
+            "invalid notation: unknown section `trump R`"
+        );
+        assert_eq!(
+            error(&format!("{} / tasks: *P1:B0 / lead P1", hands)),
+            "unknown card `B0`"
+        );
+        assert_eq!(
+            parse(&format!("{} / tasks: 1>P1:B1, 1>P2:B2 / lead P1", hands))
+                .unwrap_err()
+                .code(),
+            "duplicate_order"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+/// Computes `f(0..count)` on `threads` threads and returns the results in index order. Indices are
+/// handed out one at a time, so slow items don't hold up a whole shard.
+pub fn parallel_map<T, F>(count: usize, threads: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let next = AtomicUsize::new(0);
+
+    let mut results = thread::scope(|scope| {
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= count {
+                            return results;
+                        }
+                        results.push((i, f(i)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Computes `f` for each item on `threads` threads and hands every result to `sink` on the calling
+/// thread as soon as it is ready, so results come in the order they finish. Items are only taken
+/// from `items` when a thread is free, so it can be a stream that is still being read.
+pub fn parallel_stream<I, R, F, S>(items: I, threads: usize, f: F, mut sink: S)
+where
+    I: Iterator + Send,
+    R: Send,
+    F: Fn(I::Item) -> R + Sync,
+    S: FnMut(R),
+{
+    let items = Mutex::new(items);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            let sender = sender.clone();
+            let (items, f) = (&items, &f);
+            scope.spawn(move || loop {
+                let Some(item) = items.lock().unwrap().next() else {
+                    return;
+                };
+                if sender.send(f(item)).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(sender);
+
+        for result in receiver {
+            sink(result);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_map() {
+        assert_eq!(parallel_map(5, 3, |i| i * 2), vec![0, 2, 4, 6, 8]);
+        assert_eq!(parallel_map(3, 0, |i| i), vec![0, 1, 2]);
+        assert_eq!(parallel_map(0, 4, |i| i), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_parallel_stream() {
+        let mut results = vec![];
+        parallel_stream(0..20, 3, |i| i * 2, |r| results.push(r));
+        results.sort();
+        assert_eq!(results, (0..20).map(|i| i * 2).collect::<Vec<_>>());
+
+        let mut results = vec![];
+        parallel_stream(0..3, 0, |i| i, |r| results.push(r));
+        assert_eq!(results, vec![0, 1, 2]);
+    }
+}
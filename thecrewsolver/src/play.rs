@@ -1,4 +1,4 @@
-use crate::card::{suit, CardSet, RawCardSet};
+use crate::card::{suit, Card, CardSet, RawCardSet};
 use crate::player::{PlayerIndex, NUM_PLAYERS};
 
 pub type Hands = [CardSet; NUM_PLAYERS];
@@ -10,6 +10,16 @@ pub struct Play {
 }
 
 impl Play {
+    /// A trick made of `cards`, in the order they were played. The first card sets the lead suit.
+    pub fn new(cards: &[Card]) -> Play {
+        Play {
+            cards: CardSet::from_cards(cards),
+            lead_suit: cards
+                .first()
+                .map_or(CardSet::EMPTY, |c| CardSet::from_card(*c).get_suit()),
+        }
+    }
+
     pub fn get_trick_winner(&self) -> CardSet {
         let eligible_cards = self.lead_suit | suit::ROCKETS;
         let relevant_cards = self.cards & eligible_cards;
@@ -140,9 +150,11 @@ impl PositionCounter {
     }
 
     fn reset_with_mask(&mut self, mask: CardSet) {
-        self.mask = mask.get_raw();
-        self.hand = self.hand.rotate_left(self.position);
-        self.position = 0;
+        // Start one place before the first card, so that `increment` can land on B1.
+        let hand = self.hand.rotate_left(self.position);
+        self.position = RawCardSet::BITS - 1;
+        self.hand = hand.rotate_right(self.position);
+        self.mask = mask.get_raw().rotate_right(self.position);
         self.increment();
     }
 }
@@ -212,6 +224,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_new() {
+        let play = Play::new(&[Y2, B8, B9]);
+        assert_eq!(play.cards(), CardSet::from_cards(&[B8, B9, Y2]));
+        assert_eq!(play.get_trick_winner(), CardSet::from_card(Y2));
+    }
+
     #[test]
     fn test_position_counter() {
         let cards = CardSet::from_cards(&[B3, B5, B9, Y2, G4, R1]);
@@ -307,6 +326,24 @@ mod tests {
         assert_eq!(pg.collect::<Vec<Play>>(), expected);
     }
 
+    #[test]
+    fn test_play_generator_follows_with_b1() {
+        let hands: Hands = [
+            CardSet::from_cards(&[B9]),
+            CardSet::from_cards(&[B1, B2, Y2]),
+            CardSet::from_cards(&[Y3]),
+            CardSet::from_cards(&[Y4]),
+        ];
+
+        let followed = PlayGenerator::new(&hands, 0)
+            .map(|play| play.cards() & hands[1])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            followed,
+            vec![CardSet::from_card(B1), CardSet::from_card(B2)]
+        );
+    }
+
     #[test]
     fn test_empty_play_generator() {
         let hands: Hands = [
@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use crate::card::{Card, CardSet};
+use crate::hint::{legal_cards, safe_cards};
+use crate::play::Play;
+use crate::player::{PlayerIndex, NUM_PLAYERS};
+use crate::solver::GameState;
+
+/// One recorded card and whether the game could still be won after it.
+#[derive(Debug, PartialEq)]
+pub struct Step {
+    /// Counting from 1, including tricks played before the replay started.
+    pub trick: u8,
+    pub player: PlayerIndex,
+    pub card: Card,
+    pub winnable: bool,
+}
+
+/// The card after which the game could no longer be won.
+#[derive(Debug, PartialEq)]
+pub struct LosingCard {
+    pub trick: u8,
+    pub player: PlayerIndex,
+    pub card: Card,
+    /// The cards the player could have played instead to keep the game winnable.
+    pub alternatives: Vec<Card>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PostMortem {
+    pub winnable_at_start: bool,
+    pub steps: Vec<Step>,
+    /// `None` if the game was never winnable or was still winnable after the last recorded card.
+    pub losing_card: Option<LosingCard>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReplayError {
+    /// The card isn't in the player's hand, or they had to follow suit.
+    IllegalCard {
+        trick: u8,
+        card: Card,
+    },
+    /// A trick has more than one card per player.
+    TrickTooLong {
+        trick: u8,
+    },
+    /// A trick other than the last one is missing cards.
+    IncompleteTrick {
+        trick: u8,
+    },
+    TimedOut,
+}
+
+/// Replays the recorded tricks from `state`, each given as its cards in play order starting with
+/// the leader's, and checks after every card whether the game could still be won. The last trick
+/// may be incomplete. `timeout` applies to each card separately.
+pub fn post_mortem(
+    state: &GameState,
+    tricks: &[Vec<Card>],
+    timeout: Duration,
+) -> Result<PostMortem, ReplayError> {
+    let mut state = state.clone();
+    let mut winnable = true;
+    let mut report = PostMortem {
+        winnable_at_start: true,
+        steps: vec![],
+        losing_card: None,
+    };
+
+    for (i, cards) in tricks.iter().enumerate() {
+        let trick = state.tasks().tricks_played() + 1;
+        if cards.len() > NUM_PLAYERS {
+            return Err(ReplayError::TrickTooLong { trick });
+        }
+        if cards.len() < NUM_PLAYERS && i + 1 < tricks.len() {
+            return Err(ReplayError::IncompleteTrick { trick });
+        }
+
+        for (played, card) in cards.iter().enumerate() {
+            let player = ((state.curr_leader() as usize + played) % NUM_PLAYERS) as PlayerIndex;
+            let held = state
+                .hands()
+                .map(|h| h & !CardSet::from_cards(&cards[..played]));
+            let position = GameState::new(held, state.tasks().clone(), state.curr_leader());
+
+            // Once the game is lost there is nothing left to solve, only the record to check.
+            let options = if winnable {
+                safe_cards(&position, &cards[..played], player, timeout)
+                    .ok_or(ReplayError::TimedOut)?
+            } else {
+                legal_cards(&position, &cards[..played], player)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|c| (c, false))
+                    .collect()
+            };
+            let safe = options
+                .iter()
+                .find(|(c, _)| c == card)
+                .ok_or(ReplayError::IllegalCard { trick, card: *card })?
+                .1;
+
+            if i == 0 && played == 0 {
+                report.winnable_at_start = options.iter().any(|(_, safe)| *safe);
+                winnable = report.winnable_at_start;
+            }
+            if winnable && !safe {
+                report.losing_card = Some(LosingCard {
+                    trick,
+                    player,
+                    card: *card,
+                    alternatives: options
+                        .iter()
+                        .filter(|(_, safe)| *safe)
+                        .map(|(c, _)| *c)
+                        .collect(),
+                });
+            }
+            winnable &= safe;
+            report.steps.push(Step {
+                trick,
+                player,
+                card: *card,
+                winnable,
+            });
+        }
+
+        if cards.len() == NUM_PLAYERS {
+            let play = Play::new(cards);
+            state = match state.after_trick(&play) {
+                Some(next) => next,
+                // A task was failed, so every later position is lost as well. Carry on with the
+                // hands and leader alone to check that the rest of the record is legal.
+                None => GameState::new(
+                    state.hands().map(|h| h & !play.cards()),
+                    state.tasks().clone().with_tricks_played(trick),
+                    state.trick_winner(&play),
+                ),
+            };
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::toy_state;
+    use crate::tasks::Task;
+
+    #[test]
+    fn test_losing_card() {
+        let tricks = [vec![B1, B2, Y3, B4], vec![Y4, B9, Y2, Y7]];
+        let report = post_mortem(
+            &toy_state(Task::new(0, Y2)),
+            &tricks,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        assert!(report.winnable_at_start);
+        assert_eq!(
+            report.losing_card,
+            Some(LosingCard {
+                trick: 1,
+                player: 0,
+                card: B1,
+                alternatives: vec![B9],
+            })
+        );
+        assert_eq!(report.steps.len(), 8);
+        assert!(report.steps.iter().all(|s| !s.winnable));
+        assert_eq!(report.steps[4].player, 3);
+        assert_eq!(report.steps[4].trick, 2);
+    }
+
+    #[test]
+    fn test_won_game() {
+        let tricks = [vec![B9, B2, Y3, B4], vec![B1, Y2, Y7, Y4]];
+        let report = post_mortem(
+            &toy_state(Task::new(0, Y2)),
+            &tricks,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        assert_eq!(report.losing_card, None);
+        assert!(report.steps.iter().all(|s| s.winnable));
+    }
+
+    #[test]
+    fn test_never_winnable() {
+        let tricks = [vec![B1, B2, Y3, B4]];
+        let report = post_mortem(
+            &toy_state(Task::new(1, Y7)),
+            &tricks,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        assert!(!report.winnable_at_start);
+        assert_eq!(report.losing_card, None);
+    }
+
+    #[test]
+    fn test_illegal_card() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            post_mortem(&toy_state(Task::new(0, Y2)), &[vec![B1, Y2]], timeout),
+            Err(ReplayError::IllegalCard { trick: 1, card: Y2 })
+        );
+        assert_eq!(
+            post_mortem(&toy_state(Task::new(0, Y2)), &[vec![B1, B9]], timeout),
+            Err(ReplayError::IllegalCard { trick: 1, card: B9 })
+        );
+    }
+
+    #[test]
+    fn test_incomplete_trick() {
+        let timeout = Duration::from_secs(5);
+        let state = toy_state(Task::new(0, Y2));
+        assert_eq!(
+            post_mortem(&state, &[vec![B9, B2], vec![B9, B2, Y3, B4]], timeout),
+            Err(ReplayError::IncompleteTrick { trick: 1 })
+        );
+        let report = post_mortem(&state, &[vec![B9, B2, Y3, B4], vec![B1]], timeout).unwrap();
+        assert_eq!(report.steps.len(), 5);
+    }
+}
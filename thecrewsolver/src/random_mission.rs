@@ -0,0 +1,146 @@
+use crate::deal::{DealGenerator, TaskTemplate};
+use crate::difficulty::{rate, Difficulty};
+use crate::estimate::EstimateOptions;
+use crate::rng::Rng;
+use crate::solver::GameState;
+use crate::tasks::Token;
+
+pub struct MissionRequest {
+    pub min_tasks: usize,
+    pub max_tasks: usize,
+    pub min_score: f64,
+    pub max_score: f64,
+    pub max_attempts: usize,
+    /// Settings used to rate each candidate. The timeout also bounds the winnability check.
+    pub rating: EstimateOptions,
+}
+
+pub struct RandomMission {
+    pub state: GameState,
+    pub difficulty: Difficulty,
+    pub attempts: usize,
+}
+
+/// Deals games with random task sets until one is winnable and its difficulty score lands in the
+/// requested band. The same seed and request always try the same sequence of candidates, but
+/// whether each is accepted depends on which of its games are solved within the rating's timeout,
+/// so a slower or busier machine can settle on a different mission.
+pub fn random_mission(seed: u64, request: &MissionRequest) -> Option<RandomMission> {
+    for (i, state) in candidates(seed, request).enumerate() {
+        let Some(difficulty) = rate(&state, &request.rating) else {
+            continue;
+        };
+        if difficulty.winnable()
+            && (request.min_score..=request.max_score).contains(&difficulty.score())
+        {
+            return Some(RandomMission {
+                state,
+                difficulty,
+                attempts: i + 1,
+            });
+        }
+    }
+
+    None
+}
+
+/// The games `random_mission` tries for `seed`, in order.
+fn candidates(seed: u64, request: &MissionRequest) -> impl Iterator<Item = GameState> + '_ {
+    let mut rng = Rng::new(seed);
+    (0..request.max_attempts).map_while(move |_| {
+        let template = random_template(&mut rng, request.min_tasks, request.max_tasks);
+        DealGenerator::new(rng.next_u64()).game(&template)
+    })
+}
+
+/// Picks a task count and gives each task either no token, the next absolute or relative token,
+/// or the last-trick token if it is still free.
+fn random_template(rng: &mut Rng, min_tasks: usize, max_tasks: usize) -> TaskTemplate {
+    let num_tasks = min_tasks + rng.below(max_tasks.saturating_sub(min_tasks) + 1);
+
+    let mut tokens = vec![];
+    let mut absolute = 0;
+    let mut relative = 0;
+    let mut have_last = false;
+    for _ in 0..num_tasks {
+        match rng.below(4) {
+            0 => {
+                absolute += 1;
+                tokens.push(Token::Absolute(absolute));
+            }
+            1 => {
+                relative += 1;
+                tokens.push(Token::Relative(relative));
+            }
+            2 if !have_last => {
+                have_last = true;
+                tokens.push(Token::Last);
+            }
+            _ => {}
+        }
+    }
+
+    TaskTemplate::new(num_tasks, &tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn request(min_score: f64, max_score: f64) -> MissionRequest {
+        MissionRequest {
+            min_tasks: 1,
+            max_tasks: 2,
+            min_score,
+            max_score,
+            max_attempts: 5,
+            rating: EstimateOptions {
+                deals: 1,
+                seed: 0,
+                timeout: Duration::from_millis(500),
+                threads: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn test_random_template() {
+        let mut rng = Rng::new(3);
+        for _ in 0..100 {
+            let template = random_template(&mut rng, 2, 5);
+            assert!((2..=5).contains(&template.num_tasks));
+            assert!(template.tokens.len() <= template.num_tasks);
+            assert!(
+                template
+                    .tokens
+                    .iter()
+                    .filter(|t| **t == Token::Last)
+                    .count()
+                    <= 1
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_mission_in_band() {
+        let mission = random_mission(1, &request(0.0, 100.0)).unwrap();
+        assert!(mission.difficulty.winnable());
+        assert!(mission.attempts <= 5);
+    }
+
+    #[test]
+    fn test_candidates_are_reproducible() {
+        // Which candidate is accepted depends on the clock, but the candidates themselves don't.
+        let request = request(0.0, 100.0);
+        let games = candidates(2, &request).collect::<Vec<_>>();
+        assert_eq!(games.len(), request.max_attempts);
+        assert_eq!(games, candidates(2, &request).collect::<Vec<_>>());
+        assert_ne!(games, candidates(3, &request).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_random_mission_impossible_band() {
+        assert!(random_mission(1, &request(101.0, 102.0)).is_none());
+    }
+}
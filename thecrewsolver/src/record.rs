@@ -0,0 +1,293 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::card::{Card, CardSet};
+use crate::hint::{legal_cards, safe_cards, seat_to_move};
+use crate::play::Play;
+use crate::player::{PlayerIndex, NUM_PLAYERS};
+use crate::solver::GameState;
+
+/// What a player said about the card they showed the crew: that it is their highest, only or
+/// lowest card of its suit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Signal {
+    Highest,
+    Only,
+    Lowest,
+}
+
+impl Signal {
+    pub const ALL: [Signal; 3] = [Signal::Highest, Signal::Only, Signal::Lowest];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Signal::Highest => "highest",
+            Signal::Only => "only",
+            Signal::Lowest => "lowest",
+        }
+    }
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Signal::ALL
+            .into_iter()
+            .find(|signal| signal.name() == s)
+            .ok_or_else(|| format!("unknown signal `{}`", s))
+    }
+}
+
+/// A card a player showed the crew before `trick` (counting from 1) was led.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Communication {
+    pub trick: u8,
+    pub player: PlayerIndex,
+    pub card: Card,
+    pub signal: Signal,
+}
+
+/// What the solver doesn't need but a group archiving its games does. `mission` is only kept, the
+/// tasks of the deal are what is played.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    pub mission: Option<u8>,
+    /// One name per seat, or none at all.
+    pub players: Vec<String>,
+    /// Free text, such as `2024-05-17`.
+    pub date: Option<String>,
+}
+
+/// A whole game as it was played: the deal with its tasks, what was communicated and every trick,
+/// each starting with the leader's card. The last trick may be incomplete.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    pub metadata: Metadata,
+    pub deal: GameState,
+    pub communications: Vec<Communication>,
+    pub tricks: Vec<Vec<Card>>,
+}
+
+/// A point in a recorded game, in the form `hint::safe_cards` takes.
+#[derive(Debug, PartialEq)]
+pub struct Position {
+    /// The hands no longer hold the cards of `current_trick`.
+    pub state: GameState,
+    pub current_trick: Vec<Card>,
+    /// The trick in which a task was failed, if one was. The game can't be won after it, and
+    /// `state` only carries on the hands and the leader.
+    pub failed_trick: Option<u8>,
+}
+
+impl Position {
+    /// Whose turn it is.
+    pub fn seat(&self) -> PlayerIndex {
+        seat_to_move(&self.state, &self.current_trick)
+    }
+
+    /// Whether the crew can still win from here, or `None` if the timeout passed.
+    pub fn solve(&self, timeout: Duration) -> Option<bool> {
+        if self.failed_trick.is_some() {
+            return Some(false);
+        }
+        if self.state.is_won() {
+            return Some(true);
+        }
+        let cards = safe_cards(&self.state, &self.current_trick, self.seat(), timeout)?;
+        Some(cards.iter().any(|(_, safe)| *safe))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RecordError {
+    /// The record stops before the point asked for.
+    PastEnd { tricks: usize, cards: usize },
+    /// A trick has more than one card per player.
+    TrickTooLong { trick: u8 },
+    /// A trick other than the last one is missing cards.
+    IncompleteTrick { trick: u8 },
+    /// The card isn't in the player's hand, or they had to follow suit.
+    IllegalCard { trick: u8, card: Card },
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordError::PastEnd { tricks, cards } => write!(
+                f,
+                "the record has no point after {} tricks and {} cards",
+                tricks, cards
+            ),
+            RecordError::TrickTooLong { trick } => write!(f, "trick {} has too many cards", trick),
+            RecordError::IncompleteTrick { trick } => {
+                write!(f, "trick {} is missing cards but isn't the last one", trick)
+            }
+            RecordError::IllegalCard { trick, card } => {
+                write!(f, "{:?} can't be played in trick {}", card, trick)
+            }
+        }
+    }
+}
+
+impl GameRecord {
+    /// The last point in the record: how many whole tricks it has, and how many cards of the trick
+    /// after them.
+    pub fn end(&self) -> (usize, usize) {
+        match self.tricks.last() {
+            Some(last) if last.len() < NUM_PLAYERS => (self.tricks.len() - 1, last.len()),
+            _ => (self.tricks.len(), 0),
+        }
+    }
+
+    /// The game after its first `tricks` whole tricks and the first `cards` cards of the next one.
+    /// Every card up to that point is checked against the rules.
+    pub fn position(&self, tricks: usize, cards: usize) -> Result<Position, RecordError> {
+        if cards >= NUM_PLAYERS || (tricks, cards) > self.end() {
+            return Err(RecordError::PastEnd { tricks, cards });
+        }
+
+        let mut state = self.deal.clone();
+        let mut failed_trick = None;
+        for (i, trick_cards) in self.tricks.iter().enumerate().take(tricks + 1) {
+            let trick = state.tasks().tricks_played() + 1;
+            if trick_cards.len() > NUM_PLAYERS {
+                return Err(RecordError::TrickTooLong { trick });
+            }
+            if trick_cards.len() < NUM_PLAYERS && i + 1 < self.tricks.len() {
+                return Err(RecordError::IncompleteTrick { trick });
+            }
+
+            let played = if i < tricks { trick_cards.len() } else { cards };
+            for (j, card) in trick_cards[..played].iter().enumerate() {
+                let seat = ((state.curr_leader() as usize + j) % NUM_PLAYERS) as PlayerIndex;
+                let held = without(&state, &trick_cards[..j]);
+                if !legal_cards(&held, &trick_cards[..j], seat)
+                    .unwrap_or_default()
+                    .contains(card)
+                {
+                    return Err(RecordError::IllegalCard { trick, card: *card });
+                }
+            }
+            if i == tricks {
+                return Ok(Position {
+                    state: without(&state, &trick_cards[..cards]),
+                    current_trick: trick_cards[..cards].to_vec(),
+                    failed_trick,
+                });
+            }
+
+            let play = Play::new(trick_cards);
+            state = match state.after_trick(&play) {
+                Some(next) => next,
+                None => {
+                    failed_trick.get_or_insert(trick);
+                    GameState::new(
+                        state.hands().map(|h| h & !play.cards()),
+                        state.tasks().clone().with_tricks_played(trick),
+                        state.trick_winner(&play),
+                    )
+                }
+            };
+        }
+
+        Ok(Position {
+            state,
+            current_trick: vec![],
+            failed_trick,
+        })
+    }
+}
+
+/// `state` with `cards` taken out of the hands.
+fn without(state: &GameState, cards: &[Card]) -> GameState {
+    GameState::new(
+        state.hands().map(|h| h & !CardSet::from_cards(cards)),
+        state.tasks().clone(),
+        state.curr_leader(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::{dealt_state, toy_state, winning_line};
+    use crate::tasks::Task;
+
+    fn record(deal: GameState, tricks: Vec<Vec<Card>>) -> GameRecord {
+        GameRecord {
+            metadata: Metadata::default(),
+            deal,
+            communications: vec![],
+            tricks,
+        }
+    }
+
+    #[test]
+    fn test_positions() {
+        let timeout = Duration::from_secs(5);
+        let deal = dealt_state(2, 3);
+        let line = winning_line(&deal);
+        let record = record(deal.clone(), line.clone());
+        assert_eq!(record.end(), (line.len(), 0));
+
+        let start = record.position(0, 0).unwrap();
+        assert_eq!(start.state, deal);
+        assert_eq!(start.solve(timeout), Some(true));
+
+        let middle = record.position(1, 2).unwrap();
+        assert_eq!(middle.current_trick, line[1][..2]);
+        assert_eq!(middle.seat(), (middle.state.curr_leader() + 2) % 4);
+        assert!(!middle.state.hands()[middle.seat() as usize].contains(line[1][0]));
+        assert_eq!(middle.solve(timeout), Some(true));
+
+        let end = record.position(line.len(), 0).unwrap();
+        assert!(end.state.is_won());
+        assert_eq!(end.solve(timeout), Some(true));
+
+        assert_eq!(
+            record.position(line.len(), 1),
+            Err(RecordError::PastEnd {
+                tricks: line.len(),
+                cards: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_failed_task() {
+        // P4 wins the first trick with B4, so P1 can't win B1, and leads the next.
+        let record = record(
+            toy_state(Task::new(0, B1)),
+            vec![vec![B1, B2, Y3, B4], vec![Y4]],
+        );
+        assert_eq!(record.end(), (1, 1));
+        let position = record.position(1, 1).unwrap();
+        assert_eq!(position.failed_trick, Some(1));
+        assert_eq!(position.state.curr_leader(), 3);
+        assert_eq!(position.solve(Duration::from_secs(5)), Some(false));
+    }
+
+    #[test]
+    fn test_illegal_cards() {
+        let deal = toy_state(Task::new(0, Y2));
+        let not_following = record(deal.clone(), vec![vec![B1, Y2, Y3, B4]]);
+        assert_eq!(
+            not_following.position(1, 0),
+            Err(RecordError::IllegalCard { trick: 1, card: Y2 })
+        );
+        assert_eq!(not_following.position(0, 1).unwrap().current_trick, [B1]);
+
+        let incomplete = record(deal, vec![vec![B1, B2], vec![Y3]]);
+        assert_eq!(
+            incomplete.position(1, 0),
+            Err(RecordError::IncompleteTrick { trick: 1 })
+        );
+        assert_eq!(
+            RecordError::IllegalCard { trick: 1, card: Y2 }.to_string(),
+            "Y2 can't be played in trick 1"
+        );
+    }
+}
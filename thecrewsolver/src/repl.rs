@@ -0,0 +1,410 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use crate::card::Card;
+use crate::cli::{format_record, parse_card, parse_position, parse_record, ParseError};
+use crate::hint::{legal_cards, safe_cards, trick_start};
+use crate::human::{describe_hint, describe_position};
+use crate::player::NUM_PLAYERS;
+use crate::record::{GameRecord, Metadata, Position};
+use crate::solver::GameState;
+
+const HELP: &str = "\
+load FILE     read a game, position or game record from a file
+deal TEXT     start a game written in the compact notation
+show          show the hands, the tasks and the trick so far
+B3 Y4 ...     play cards, each by the seat whose turn it is
+undo [N]      take back the last card, or the last N
+winnable      whether the crew can still win
+wins          which cards the next player can play and still win
+branch NAME   copy the cards played so far under a new name and switch to it
+switch NAME   go back to another branch
+branches      list the branches
+save FILE     write the current branch as a game record
+quit          leave";
+
+/// The cards played from the deal in one line of play.
+struct Branch {
+    name: String,
+    cards: Vec<Card>,
+}
+
+/// An interactive look at one deal. Cards are played one at a time, by whoever's turn it is, and
+/// every branch starts from the same deal.
+pub struct Session {
+    deal: Option<GameState>,
+    branches: Vec<Branch>,
+    current: usize,
+    /// How long `winnable` and `wins` may take.
+    timeout: Duration,
+}
+
+impl Session {
+    pub fn new(timeout: Duration) -> Session {
+        Session {
+            deal: None,
+            branches: vec![],
+            current: 0,
+            timeout,
+        }
+    }
+
+    /// Runs one command and returns what to print, or why it couldn't be run.
+    pub fn execute(&mut self, line: &str) -> Result<String, String> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let Some((command, rest)) = words.split_first() else {
+            return Ok(String::new());
+        };
+        match *command {
+            "help" => Ok(HELP.to_string()),
+            "load" => {
+                let path = argument(rest, "load FILE")?;
+                let input = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+                self.load(&input)
+            }
+            "deal" => self.load(&rest.join(" ")),
+            "show" => self.show(),
+            "undo" => match rest.first() {
+                Some(n) => self.undo(n.parse().map_err(|_| format!("`{}` isn't a number", n))?),
+                None => self.undo(1),
+            },
+            "winnable" => Ok(match self.position()?.solve(self.timeout) {
+                Some(true) => "Winnable".to_string(),
+                Some(false) => "Not winnable".to_string(),
+                None => "Timed out".to_string(),
+            }),
+            "wins" => self.wins(),
+            "branch" => self.branch(argument(rest, "branch NAME")?),
+            "switch" => self.switch(argument(rest, "switch NAME")?),
+            "branches" => Ok(self
+                .branches
+                .iter()
+                .enumerate()
+                .map(|(i, b)| {
+                    let mark = if i == self.current { '*' } else { ' ' };
+                    format!("{} {} ({} cards)", mark, b.name, b.cards.len())
+                })
+                .collect::<Vec<_>>()
+                .join("\n")),
+            "save" => {
+                let path = argument(rest, "save FILE")?;
+                fs::write(path, format_record(&self.record()?))
+                    .map_err(|e| format!("{}: {}", path, e))?;
+                Ok(format!("Saved to {}", path))
+            }
+            _ if parse_card(&command.to_uppercase()).is_ok() => self.play(&words),
+            _ => Err(format!("unknown command `{}`; type `help`", command)),
+        }
+    }
+
+    /// Starts over from a game, a position with a trick in progress or a game record, whose
+    /// cards become the `main` branch.
+    fn load(&mut self, input: &str) -> Result<String, String> {
+        let (deal, cards) = if input.contains("\"deal\"") {
+            let record = parse_record(input).map_err(|e| e.to_string())?;
+            (record.deal, record.tricks.concat())
+        } else {
+            let position = parse_position(input).map_err(|e| e.to_string())?;
+            if let Some(trick) = position.failed_trick {
+                return Err(ParseError::GameLost(trick).to_string());
+            }
+            let start = trick_start(&position.state, &position.current_trick)
+                .ok_or("the trick in progress couldn't have been played")?;
+            (start, position.current_trick)
+        };
+        let record = record(&deal, &cards);
+        let (tricks, played) = record.end();
+        record.position(tricks, played).map_err(|e| e.to_string())?;
+
+        self.deal = Some(deal);
+        self.branches = vec![Branch {
+            name: "main".to_string(),
+            cards,
+        }];
+        self.current = 0;
+        self.show()
+    }
+
+    fn show(&self) -> Result<String, String> {
+        let position = self.position()?;
+        Ok(format!(
+            "{}\nBranch {}: {}",
+            describe_position(&position),
+            self.branches[self.current].name,
+            turn(&position)
+        ))
+    }
+
+    /// Plays the cards in turn. If one can't be played, none of them are.
+    fn play(&mut self, words: &[&str]) -> Result<String, String> {
+        self.position()?;
+        let played = self.branches[self.current].cards.len();
+        let result = words
+            .iter()
+            .map(|word| self.play_card(word))
+            .collect::<Result<Vec<_>, _>>();
+        match result {
+            Ok(lines) => {
+                let mut lines = lines.into_iter().flatten().collect::<Vec<_>>();
+                lines.push(turn(&self.position()?));
+                Ok(lines.join("\n"))
+            }
+            Err(error) => {
+                self.branches[self.current].cards.truncate(played);
+                Err(error)
+            }
+        }
+    }
+
+    /// Plays one card, and says who won the trick if it was the last card of one.
+    fn play_card(&mut self, word: &str) -> Result<Option<String>, String> {
+        let position = self.position()?;
+        let card = parse_card(&word.to_uppercase()).map_err(|e| e.to_string())?;
+        if position.state.hands().iter().all(|h| h.num_set() == 0) {
+            return Err("the game is over".to_string());
+        }
+        let seat = position.seat();
+        let legal = legal_cards(&position.state, &position.current_trick, seat).unwrap_or_default();
+        if !legal.contains(&card) {
+            return Err(format!(
+                "P{} can't play {:?}; they can play {}",
+                seat + 1,
+                card,
+                legal
+                    .iter()
+                    .map(|c| format!("{:?}", c))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ));
+        }
+
+        self.branches[self.current].cards.push(card);
+        if position.current_trick.len() + 1 < NUM_PLAYERS {
+            return Ok(None);
+        }
+        let next = self.position()?;
+        Ok(Some(format!(
+            "P{} wins trick {}",
+            next.state.curr_leader() + 1,
+            position.state.tasks().tricks_played() + 1
+        )))
+    }
+
+    fn undo(&mut self, count: usize) -> Result<String, String> {
+        let cards = &mut self
+            .branches
+            .get_mut(self.current)
+            .ok_or("no game loaded")?
+            .cards;
+        if count == 0 || count > cards.len() {
+            return Err(format!("only {} cards can be taken back", cards.len()));
+        }
+        cards.truncate(cards.len() - count);
+        Ok(turn(&self.position()?))
+    }
+
+    fn wins(&self) -> Result<String, String> {
+        let position = self.position()?;
+        if position.failed_trick.is_some() || position.state.is_won() {
+            return Ok(turn(&position));
+        }
+        let seat = position.seat();
+        let cards = safe_cards(&position.state, &position.current_trick, seat, self.timeout)
+            .ok_or("timed out")?;
+        Ok(format!("P{} to play\n{}", seat + 1, describe_hint(&cards)))
+    }
+
+    fn branch(&mut self, name: &str) -> Result<String, String> {
+        self.position()?;
+        if self.branches.iter().any(|b| b.name == name) {
+            return Err(format!("there is already a branch `{}`", name));
+        }
+        self.branches.push(Branch {
+            name: name.to_string(),
+            cards: self.branches[self.current].cards.clone(),
+        });
+        self.current = self.branches.len() - 1;
+        Ok(format!("Now on branch {}", name))
+    }
+
+    fn switch(&mut self, name: &str) -> Result<String, String> {
+        self.current = self
+            .branches
+            .iter()
+            .position(|b| b.name == name)
+            .ok_or_else(|| format!("no branch `{}`", name))?;
+        Ok(format!(
+            "Now on branch {}\n{}",
+            name,
+            turn(&self.position()?)
+        ))
+    }
+
+    /// The current branch as a game record.
+    pub fn record(&self) -> Result<GameRecord, String> {
+        let deal = self
+            .deal
+            .as_ref()
+            .ok_or("no game loaded; use `load FILE` or `deal TEXT`")?;
+        Ok(record(deal, &self.branches[self.current].cards))
+    }
+
+    /// The end of the current branch.
+    pub fn position(&self) -> Result<Position, String> {
+        let record = self.record()?;
+        let (tricks, cards) = record.end();
+        record.position(tricks, cards).map_err(|e| e.to_string())
+    }
+}
+
+/// Reads commands from `input` until `quit` or the end of the input, writing a prompt before each
+/// and what it printed after it.
+pub fn run<R: BufRead, W: Write>(
+    session: &mut Session,
+    input: R,
+    output: &mut W,
+) -> io::Result<()> {
+    write!(output, "> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        if matches!(line.trim(), "quit" | "exit") {
+            return Ok(());
+        }
+        match session.execute(&line) {
+            Ok(text) if text.is_empty() => {}
+            Ok(text) => writeln!(output, "{}", text)?,
+            Err(error) => writeln!(output, "error: {}", error)?,
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    writeln!(output)
+}
+
+fn record(deal: &GameState, cards: &[Card]) -> GameRecord {
+    GameRecord {
+        metadata: Metadata::default(),
+        deal: deal.clone(),
+        communications: vec![],
+        tricks: cards.chunks(NUM_PLAYERS).map(<[Card]>::to_vec).collect(),
+    }
+}
+
+fn turn(position: &Position) -> String {
+    if position.failed_trick.is_some() {
+        "The game is lost".to_string()
+    } else if position.state.is_won() {
+        "All tasks are done".to_string()
+    } else {
+        format!("P{} to play", position.seat() + 1)
+    }
+}
+
+fn argument<'a>(rest: &[&'a str], usage: &str) -> Result<&'a str, String> {
+    match rest {
+        [argument] => Ok(argument),
+        _ => Err(format!("usage: {}", usage)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEAL: &str =
+        "deal P1: B1 B9 / P2: B2 Y2 / P3: Y3 Y7 / P4: B4 Y4 / tasks: *P1:Y2 / lead P1";
+
+    fn session() -> Session {
+        let mut session = Session::new(Duration::from_secs(5));
+        session.execute(DEAL).unwrap();
+        session
+    }
+
+    #[test]
+    fn test_play() {
+        let mut session = Session::new(Duration::from_secs(5));
+        assert!(session.execute("show").unwrap_err().starts_with("no game"));
+        assert!(session.execute("B1").unwrap_err().starts_with("no game"));
+        assert!(session.execute(DEAL).unwrap().contains("P1 (leads): B1 B9"));
+
+        assert_eq!(
+            session.execute("wins"),
+            Ok("P1 to play\nSafe: B9\nUnsafe: B1".to_string())
+        );
+        assert_eq!(
+            session.execute("b9 y2"),
+            Err("P2 can't play Y2; they can play B2".to_string())
+        );
+        assert_eq!(session.execute("B9 B2"), Ok("P3 to play".to_string()));
+        assert_eq!(
+            session.execute("Y3 B4"),
+            Ok("P1 wins trick 1\nP1 to play".to_string())
+        );
+        assert_eq!(
+            session.execute("B1 Y2 Y7 Y4"),
+            Ok("P1 wins trick 2\nAll tasks are done".to_string())
+        );
+        assert_eq!(session.execute("B1"), Err("the game is over".to_string()));
+        assert_eq!(
+            session.execute("hello"),
+            Err("unknown command `hello`; type `help`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_undo_and_branches() {
+        let mut session = session();
+        session.execute("B9 B2 Y3 B4").unwrap();
+        assert_eq!(session.execute("winnable"), Ok("Winnable".to_string()));
+        assert_eq!(
+            session.execute("branch alt"),
+            Ok("Now on branch alt".to_string())
+        );
+        assert_eq!(session.execute("undo 4"), Ok("P1 to play".to_string()));
+        assert!(session.execute("undo 5").is_err());
+        assert_eq!(
+            session.execute("B1 B2 Y3 B4 Y4 B9 Y2 Y7"),
+            Ok("P4 wins trick 1\nP3 wins trick 2\nThe game is lost".to_string())
+        );
+        assert_eq!(session.execute("winnable"), Ok("Not winnable".to_string()));
+        assert_eq!(
+            session.execute("branches"),
+            Ok("  main (4 cards)\n* alt (8 cards)".to_string())
+        );
+        assert_eq!(
+            session.execute("switch main"),
+            Ok("Now on branch main\nP1 to play".to_string())
+        );
+        assert!(session.execute("branch main").is_err());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("thecrewsolver-{}-repl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut session = session();
+        session.execute("B9 B2 Y3").unwrap();
+        session.execute(&format!("save {}", path)).unwrap();
+
+        let mut loaded = Session::new(Duration::from_secs(5));
+        let shown = loaded.execute(&format!("load {}", path)).unwrap();
+        assert!(shown.contains("Trick so far: B9 B2 Y3, P4 to play"));
+        assert_eq!(
+            loaded.execute("B4"),
+            Ok("P1 wins trick 1\nP1 to play".to_string())
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_run() {
+        let input = format!("{}\n\nwinnable\nquit\nshow\n", DEAL);
+        let mut output = vec![];
+        run(&mut session(), input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with("> Winnable\n> "));
+        assert_eq!(output.matches("> ").count(), 4);
+    }
+}
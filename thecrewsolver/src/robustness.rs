@@ -0,0 +1,236 @@
+use serde::Serialize;
+use std::str::FromStr;
+
+use crate::card::{suit, Card, CardSet};
+use crate::hint::legal_cards;
+use crate::play::Play;
+use crate::player::{PlayerIndex, NUM_PLAYERS};
+use crate::rng::Rng;
+use crate::solver::GameState;
+
+/// How every seat picks its card.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Policy {
+    /// Any legal card, uniformly.
+    Random,
+    /// Tries to win task cards it owns and to stay under task cards owned by others, and hands
+    /// over task cards meant for someone else when it can. Otherwise plays low.
+    GreedyToTask,
+    /// The lowest legal card, picking randomly between equal values.
+    LowestCard,
+}
+
+impl Policy {
+    pub const ALL: [Policy; 3] = [Policy::Random, Policy::GreedyToTask, Policy::LowestCard];
+
+    /// The name used on the command line and in JSON.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Policy::Random => "random",
+            Policy::GreedyToTask => "greedy-to-task",
+            Policy::LowestCard => "lowest-card",
+        }
+    }
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Policy::ALL
+            .into_iter()
+            .find(|p| p.name() == s)
+            .ok_or_else(|| format!("unknown policy `{}`", s))
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PolicyResult {
+    pub policy: Policy,
+    pub games: usize,
+    pub won: usize,
+    pub success_rate: f64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RobustnessReport {
+    pub policies: Vec<PolicyResult>,
+}
+
+impl RobustnessReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Plays the deal `games` times with each policy used by all four seats and counts how often the
+/// crew wins. Each policy's games draw from their own generator seeded with `seed`.
+pub fn robustness(
+    state: &GameState,
+    policies: &[Policy],
+    games: usize,
+    seed: u64,
+) -> RobustnessReport {
+    RobustnessReport {
+        policies: policies
+            .iter()
+            .map(|policy| {
+                let mut rng = Rng::new(seed);
+                let won = (0..games)
+                    .filter(|_| play_out(state, *policy, &mut rng))
+                    .count();
+                PolicyResult {
+                    policy: *policy,
+                    games,
+                    won,
+                    success_rate: if games == 0 {
+                        0.0
+                    } else {
+                        won as f64 / games as f64
+                    },
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Plays one game to the end and returns whether the crew won.
+pub fn play_out(state: &GameState, policy: Policy, rng: &mut Rng) -> bool {
+    let mut state = state.clone();
+    while !state.is_won() {
+        let mut cards = vec![];
+        for i in 0..NUM_PLAYERS {
+            let seat = ((state.curr_leader() as usize + i) % NUM_PLAYERS) as PlayerIndex;
+            let held = state.hands().map(|h| h & !CardSet::from_cards(&cards));
+            let position = GameState::new(held, state.tasks().clone(), state.curr_leader());
+            let legal = legal_cards(&position, &cards, seat).unwrap_or_default();
+            if legal.is_empty() {
+                // Out of cards with tasks left.
+                return false;
+            }
+            cards.push(choose(&state, seat, &cards, &legal, policy, rng));
+        }
+
+        state = match state.after_trick(&Play::new(&cards)) {
+            Some(next) => next,
+            None => return false,
+        };
+    }
+    true
+}
+
+fn choose(
+    state: &GameState,
+    seat: PlayerIndex,
+    trick: &[Card],
+    legal: &[Card],
+    policy: Policy,
+    rng: &mut Rng,
+) -> Card {
+    match policy {
+        Policy::Random => legal[rng.below(legal.len())],
+        Policy::LowestCard => lowest(legal, rng),
+        Policy::GreedyToTask => {
+            let tasks = state.tasks().to_tokens();
+            let owner = |card: &Card| {
+                tasks
+                    .iter()
+                    .find(|(t, _)| t.card() == *card)
+                    .map(|(t, _)| t.player())
+            };
+
+            if let Some(player) = trick.iter().find_map(owner) {
+                return if player == seat {
+                    highest(legal)
+                } else {
+                    lowest(legal, rng)
+                };
+            }
+            let gifts = legal
+                .iter()
+                .copied()
+                .filter(|c| owner(c).is_some_and(|p| p != seat))
+                .collect::<Vec<_>>();
+            if !gifts.is_empty() {
+                return gifts[rng.below(gifts.len())];
+            }
+            let needs_tricks = tasks.iter().any(|(t, _)| {
+                t.player() == seat && !state.hands()[seat as usize].contains(t.card())
+            });
+            if needs_tricks {
+                highest(legal)
+            } else {
+                lowest(legal, rng)
+            }
+        }
+    }
+}
+
+/// Card value, with rockets above every suit.
+fn rank(card: Card) -> u8 {
+    if suit::ROCKETS.contains(card) {
+        10 + card as u8 - Card::R1 as u8
+    } else {
+        card as u8 % 9 + 1
+    }
+}
+
+fn highest(cards: &[Card]) -> Card {
+    *cards.iter().max_by_key(|c| rank(**c)).unwrap()
+}
+
+fn lowest(cards: &[Card], rng: &mut Rng) -> Card {
+    let low = cards.iter().map(|c| rank(*c)).min().unwrap();
+    let lowest = cards
+        .iter()
+        .copied()
+        .filter(|c| rank(*c) == low)
+        .collect::<Vec<_>>();
+    lowest[rng.below(lowest.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::toy_state;
+    use crate::tasks::Task;
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(rank(B1), 1);
+        assert_eq!(rank(G9), 9);
+        assert_eq!(rank(R1), 10);
+        assert!(rank(R4) > rank(R3));
+    }
+
+    #[test]
+    fn test_robustness() {
+        // Winning needs B9 led first; the lowest card policy always leads B1.
+        let report = robustness(&toy_state(Task::new(0, Y2)), &Policy::ALL, 200, 0);
+        let rates = report
+            .policies
+            .iter()
+            .map(|p| (p.policy, p.won))
+            .collect::<Vec<_>>();
+        assert_eq!(rates[1], (Policy::GreedyToTask, 200));
+        assert_eq!(rates[2], (Policy::LowestCard, 0));
+        assert!(rates[0].1 > 50 && rates[0].1 < 150);
+
+        let report = robustness(&toy_state(Task::new(1, Y7)), &Policy::ALL, 50, 0);
+        assert!(report.policies.iter().all(|p| p.won == 0));
+    }
+
+    #[test]
+    fn test_seed() {
+        let state = toy_state(Task::new(0, Y2));
+        let random_wins = |seed| robustness(&state, &[Policy::Random], 100, seed).policies[0].won;
+        assert_eq!(random_wins(7), random_wins(7));
+
+        let report = robustness(&state, &[Policy::Random], 100, 7);
+        let result = &report.policies[0];
+        assert_eq!(result.games, 100);
+        assert_eq!(result.success_rate, result.won as f64 / 100.0);
+    }
+}
@@ -0,0 +1,197 @@
+use serde_json::{json, Value};
+
+use crate::card::Card;
+use crate::cli::{card_to_str, SCHEMA_VERSION};
+use crate::player::NUM_PLAYERS;
+use crate::tasks::{MAX_TASKS, NUM_TRICKS};
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The JSON Schema of a game as `cli::parse` reads it, in the current version. Older games are
+/// accepted too, as `version` only allows versions the solver can still read.
+pub fn input_schema() -> Value {
+    let player = json!({ "type": "integer", "minimum": 0, "maximum": NUM_PLAYERS - 1 });
+    let trick = json!({ "type": "integer", "minimum": 1, "maximum": NUM_TRICKS });
+    json!({
+        "$schema": DIALECT,
+        "title": "The Crew game",
+        "description": "A game for the solver: the hands, the tasks and who leads the next trick.",
+        "type": "object",
+        "required": ["hands", "tasks", "curr_leader"],
+        "properties": {
+            "version": {
+                "description": "The schema version; games without one are version 1.",
+                "type": "integer",
+                "minimum": 1,
+                "maximum": SCHEMA_VERSION,
+            },
+            "id": {
+                "description": "Any value, returned with the result in batch mode.",
+            },
+            "hands": {
+                "description": "The cards each player holds, by seat.",
+                "type": "array",
+                "minItems": NUM_PLAYERS,
+                "maxItems": NUM_PLAYERS,
+                "items": { "type": "array", "items": card(), "uniqueItems": true },
+            },
+            "tasks": {
+                "type": "array",
+                "maxItems": MAX_TASKS,
+                "items": {
+                    "type": "object",
+                    "required": ["card", "player_num"],
+                    "properties": {
+                        "task_type": {
+                            "description": "Left out with `mission`, to hand out the mission's tokens in order.",
+                            "enum": ["absolute", "relative", "anytime", "last"],
+                        },
+                        "order": {
+                            "description": "The token's number, for absolute and relative tasks.",
+                            "type": "integer",
+                            "minimum": 0,
+                        },
+                        "card": card(),
+                        "player_num": player,
+                        "earliest": trick,
+                        "latest": trick,
+                    },
+                    "additionalProperties": false,
+                },
+            },
+            "curr_leader": player,
+            "tricks_played": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": NUM_TRICKS,
+            },
+            "mission": {
+                "description": "A logbook mission whose tokens the tasks must match.",
+                "type": "integer",
+                "minimum": 1,
+            },
+            "current_trick": {
+                "description": "Cards already played in the trick in progress, starting with the leader's, for `hint`.",
+                "type": "array",
+                "maxItems": NUM_PLAYERS - 1,
+                "items": card(),
+            },
+            "tricks": {
+                "description": "Tricks played from the game, each starting with the leader's card, for `replay`.",
+                "type": "array",
+                "items": { "type": "array", "maxItems": NUM_PLAYERS, "items": card() },
+            },
+            "winners": {
+                "description": "Who was recorded as winning each of `tricks`.",
+                "type": "array",
+                "items": player,
+            },
+        },
+        "additionalProperties": false,
+    })
+}
+
+/// The JSON Schema of the solver's result, as `cli::RunOutput` writes it.
+pub fn output_schema() -> Value {
+    json!({
+        "$schema": DIALECT,
+        "title": "The Crew solver result",
+        "type": "object",
+        "required": ["version", "success", "result", "duration"],
+        "properties": {
+            "version": { "const": SCHEMA_VERSION },
+            "id": {
+                "description": "The game's `id`, in batch mode.",
+            },
+            "success": {
+                "description": "Whether the solver finished within its time.",
+                "type": "boolean",
+            },
+            "result": {
+                "description": "Whether the game can be won; false unless `success`.",
+                "type": "boolean",
+            },
+            "duration": {
+                "description": "Milliseconds spent solving.",
+                "type": "integer",
+                "minimum": 0,
+            },
+            "error": {
+                "description": "Why the game couldn't be read.",
+                "type": "object",
+                "required": ["code", "message"],
+                "properties": {
+                    "code": { "type": "string" },
+                    "message": { "type": "string" },
+                },
+                "additionalProperties": false,
+            },
+        },
+        "additionalProperties": false,
+    })
+}
+
+fn card() -> Value {
+    json!({ "enum": Card::ALL.map(card_to_str).to_vec() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::cli::{format_state, ParseError, RunOutput};
+    use crate::fixtures::{dealt_state, toy_state};
+    use crate::tasks::Task;
+
+    /// Checks the parts of a schema that the documents here use: required and allowed
+    /// properties, nested objects and arrays of objects.
+    fn conforms(schema: &Value, document: &Value) -> bool {
+        let Some(object) = document.as_object() else {
+            return schema.get("type") != Some(&json!("object"));
+        };
+        let properties = &schema["properties"];
+        let required = schema["required"].as_array().cloned().unwrap_or_default();
+        required
+            .iter()
+            .all(|r| object.contains_key(r.as_str().unwrap()))
+            && object.iter().all(|(key, value)| {
+                let Some(property) = properties.get(key) else {
+                    return false;
+                };
+                match value.as_array() {
+                    Some(items) => items.iter().all(|i| conforms(&property["items"], i)),
+                    None => conforms(property, value),
+                }
+            })
+    }
+
+    #[test]
+    fn test_input_schema() {
+        let schema = input_schema();
+        for state in [toy_state(Task::new(0, Y2)), dealt_state(1, 5)] {
+            let json = format_state(&state, None);
+            assert!(conforms(&schema, &serde_json::from_str(&json).unwrap()));
+        }
+        let extra = json!({ "hands": [], "tasks": [], "curr_leader": 0, "trump": "R" });
+        assert!(!conforms(&schema, &extra));
+        assert_eq!(
+            schema["properties"]["hands"]["items"]["items"]["enum"][0],
+            "B1"
+        );
+        assert_eq!(schema["properties"]["version"]["maximum"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_output_schema() {
+        let schema = output_schema();
+        for output in [
+            RunOutput::new(true, false, 12),
+            RunOutput::invalid(&ParseError::HandCount(3)).with_id(json!("a")),
+        ] {
+            assert!(conforms(
+                &schema,
+                &serde_json::from_str(&output.to_json()).unwrap()
+            ));
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use crate::card::Card;
+use crate::play::{Hands, Play};
+use crate::player::PlayerIndex;
+use crate::solver::{GameState, Search};
+use crate::tasks::{TasksObjective, Token};
+
+pub struct TreeOptions {
+    /// Tricks below the starting position that are recorded; deeper positions are only solved.
+    pub max_depth: u8,
+    /// Nodes recorded at most, counting the starting position.
+    pub max_nodes: usize,
+    pub timeout: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Won,
+    Lost,
+    /// The trick itself failed a task.
+    Failed,
+    /// The position was already seen lost, so it wasn't searched again.
+    Cached,
+    /// Not searched because an earlier trick from the same position already wins.
+    Pruned,
+}
+
+/// A trick leading to a node.
+#[derive(Debug)]
+pub struct TreeTrick {
+    /// Counting from 1, including tricks played before the search started.
+    pub number: u8,
+    /// Each card of the trick with the player who played it, starting with the leader.
+    pub cards: Vec<(PlayerIndex, Card)>,
+    pub winner: PlayerIndex,
+}
+
+#[derive(Debug)]
+pub struct TreeNode {
+    pub parent: Option<usize>,
+    /// `None` for the starting position.
+    pub trick: Option<TreeTrick>,
+    /// Hands and leader after the trick.
+    pub hands: Hands,
+    pub leader: PlayerIndex,
+    /// Tasks left after the trick, or `None` if it failed one.
+    pub tasks: Option<TasksObjective>,
+    pub outcome: Outcome,
+    /// False if the node's children weren't recorded because of the depth or node limit. Its
+    /// outcome is still solved.
+    pub expanded: bool,
+}
+
+pub struct SearchTree {
+    /// Node 0 is the starting position; every other node comes after its parent.
+    pub nodes: Vec<TreeNode>,
+}
+
+/// Searches `state` the way the solver does, trying tricks in order and stopping at the first one
+/// that wins, and records the tricks it looks at. Returns `None` if the timeout passed.
+pub fn search_tree(state: &GameState, options: &TreeOptions) -> Option<SearchTree> {
+    let deadline = Instant::now() + options.timeout;
+    let mut recorder = Recorder {
+        options,
+        deadline,
+        search: Search::new(Some(deadline)),
+        lost: vec![],
+        nodes: vec![TreeNode {
+            parent: None,
+            trick: None,
+            hands: *state.hands(),
+            leader: state.curr_leader(),
+            tasks: Some(state.tasks().clone()),
+            outcome: Outcome::Won,
+            expanded: true,
+        }],
+    };
+    if !state.is_won() {
+        recorder.visit(0, state, 0)?;
+    }
+    Some(SearchTree {
+        nodes: recorder.nodes,
+    })
+}
+
+struct Recorder<'a> {
+    options: &'a TreeOptions,
+    deadline: Instant,
+    /// Solves the positions that aren't recorded.
+    search: Search,
+    lost: Vec<GameState>,
+    nodes: Vec<TreeNode>,
+}
+
+impl Recorder<'_> {
+    /// Searches the position of node `id`, recording its children, and sets its outcome.
+    fn visit(&mut self, id: usize, state: &GameState, depth: u8) -> Option<bool> {
+        if Instant::now() >= self.deadline {
+            return None;
+        }
+        if self.lost.contains(state) {
+            self.nodes[id].outcome = Outcome::Cached;
+            return Some(false);
+        }
+        if depth >= self.options.max_depth {
+            self.nodes[id].expanded = false;
+            return self.solved(id, state);
+        }
+
+        let mut won = false;
+        let mut plays = state.plays();
+        for play in plays.by_ref() {
+            if self.nodes.len() >= self.options.max_nodes {
+                self.nodes[id].expanded = false;
+                won = won || self.search.solve_play(state, &play)?;
+                break;
+            }
+
+            let child = self.nodes.len();
+            let next = state.after_trick(&play);
+            self.nodes.push(TreeNode {
+                parent: Some(id),
+                trick: Some(trick(state, &play)),
+                hands: state.hands().map(|h| h & !play.cards()),
+                leader: state.trick_winner(&play),
+                tasks: next.as_ref().map(|n| n.tasks().clone()),
+                outcome: Outcome::Pruned,
+                expanded: true,
+            });
+            if won {
+                continue;
+            }
+            won = match next {
+                None => {
+                    self.nodes[child].outcome = Outcome::Failed;
+                    false
+                }
+                Some(next) if next.is_won() => {
+                    self.nodes[child].outcome = Outcome::Won;
+                    true
+                }
+                Some(next) => self.visit(child, &next, depth + 1)?,
+            };
+        }
+        // The tricks past the node limit still decide the outcome.
+        for play in plays {
+            if won {
+                break;
+            }
+            won = self.search.solve_play(state, &play)?;
+        }
+
+        if !won {
+            self.lost.push(state.clone());
+        }
+        self.nodes[id].outcome = if won { Outcome::Won } else { Outcome::Lost };
+        Some(won)
+    }
+
+    fn solved(&mut self, id: usize, state: &GameState) -> Option<bool> {
+        let won = self.search.solve(state)?;
+        self.nodes[id].outcome = if won { Outcome::Won } else { Outcome::Lost };
+        Some(won)
+    }
+}
+
+fn trick(state: &GameState, play: &Play) -> TreeTrick {
+    TreeTrick {
+        number: state.tasks().tricks_played() + 1,
+        cards: state.cards_by_seat(play),
+        winner: state.trick_winner(play),
+    }
+}
+
+impl SearchTree {
+    /// The tree as a Graphviz digraph. Won nodes are green, lost ones red, tricks that failed a
+    /// task orange, positions already known to be lost grey and pruned tricks white. Nodes whose
+    /// children weren't recorded have a dashed border.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from(
+            "digraph search {\n  node [shape=box, style=filled, fontname=monospace];\n",
+        );
+        for (id, node) in self.nodes.iter().enumerate() {
+            let colour = match node.outcome {
+                Outcome::Won => "palegreen",
+                Outcome::Lost => "lightcoral",
+                Outcome::Failed => "orange",
+                Outcome::Cached => "lightgrey",
+                Outcome::Pruned => "white",
+            };
+            let style = if node.expanded {
+                "filled"
+            } else {
+                "filled,dashed"
+            };
+            writeln!(
+                dot,
+                "  n{} [label=\"{}\", fillcolor={}, style=\"{}\"];",
+                id,
+                label(node),
+                colour,
+                style
+            )
+            .unwrap();
+            if let Some(parent) = node.parent {
+                writeln!(dot, "  n{} -> n{};", parent, id).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Node label, one fact per line (`\l` left-justifies each line in Graphviz).
+fn label(node: &TreeNode) -> String {
+    let mut lines = vec![];
+    match &node.trick {
+        Some(trick) => {
+            let cards = trick
+                .cards
+                .iter()
+                .map(|(player, card)| format!("P{} {:?}", player + 1, card))
+                .collect::<Vec<_>>();
+            lines.push(format!("Trick {}: {}", trick.number, cards.join(", ")));
+            lines.push(format!("P{} wins", trick.winner + 1));
+        }
+        None => lines.push("Start".to_string()),
+    }
+    for (player, hand) in node.hands.iter().enumerate() {
+        let cards = hand
+            .cards()
+            .iter()
+            .map(|c| format!("{:?}", c))
+            .collect::<Vec<_>>();
+        let lead = if player as PlayerIndex == node.leader {
+            " (leads)"
+        } else {
+            ""
+        };
+        lines.push(format!("P{}{}: {}", player + 1, lead, cards.join(" ")));
+    }
+    match &node.tasks {
+        Some(tasks) => {
+            let tasks = tasks
+                .to_tokens()
+                .iter()
+                .map(|(task, token)| {
+                    let token = match token {
+                        Some(Token::Absolute(n)) => format!(" #{}", n),
+                        Some(Token::Relative(n)) => format!(" {}", ">".repeat(*n as usize)),
+                        Some(Token::Last) => " Ω".to_string(),
+                        None => String::new(),
+                    };
+                    format!("P{} {:?}{}", task.player() + 1, task.card(), token)
+                })
+                .collect::<Vec<_>>();
+            lines.push(format!("Tasks: {}", tasks.join(", ")));
+        }
+        None => lines.push("Task failed".to_string()),
+    }
+    lines.push(format!("{:?}", node.outcome));
+
+    lines.iter().map(|l| format!("{}\\l", l)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::toy_state;
+    use crate::tasks::Task;
+
+    fn options(max_depth: u8, max_nodes: usize) -> TreeOptions {
+        TreeOptions {
+            max_depth,
+            max_nodes,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn test_search_tree() {
+        let tree = search_tree(&toy_state(Task::new(0, Y2)), &options(10, 100)).unwrap();
+        let outcomes = tree.nodes.iter().map(|n| n.outcome).collect::<Vec<_>>();
+        assert_eq!(outcomes[0], Outcome::Won);
+        assert!(outcomes.contains(&Outcome::Failed));
+        assert!(tree.nodes.iter().all(|n| n.expanded));
+
+        let first = tree.nodes[1].trick.as_ref().unwrap();
+        assert_eq!(first.number, 1);
+        assert_eq!(first.cards[0], (0, B1));
+
+        // Every trick after the first winning one from a position is pruned.
+        for (id, node) in tree.nodes.iter().enumerate() {
+            let siblings = tree.nodes[id + 1..]
+                .iter()
+                .filter(|n| n.parent == node.parent && n.parent.is_some());
+            if node.outcome == Outcome::Won && node.parent.is_some() {
+                assert!(siblings.map(|n| n.outcome).all(|o| o == Outcome::Pruned));
+            }
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let tree = search_tree(&toy_state(Task::new(0, Y2)), &options(0, 100)).unwrap();
+        assert_eq!(tree.nodes.len(), 1);
+        assert_eq!(tree.nodes[0].outcome, Outcome::Won);
+        assert!(!tree.nodes[0].expanded);
+
+        let tree = search_tree(&toy_state(Task::new(1, Y7)), &options(10, 3)).unwrap();
+        assert_eq!(tree.nodes.len(), 3);
+        assert_eq!(tree.nodes[0].outcome, Outcome::Lost);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let tree = search_tree(&toy_state(Task::new(1, Y7)), &options(1, 100)).unwrap();
+        let dot = tree.to_dot();
+        assert!(dot.starts_with("digraph search {"));
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.contains("fillcolor=lightcoral"));
+        assert!(dot.contains("Trick 1: P1 B1, P2 B2, P3 Y3, P4 B4\\lP4 wins\\l"));
+    }
+}
@@ -1,9 +1,13 @@
-use crate::card::CardSet;
+use crate::card::{Card, CardSet};
 use crate::play::{Hands, Play, PlayGenerator};
 use crate::player::PlayerIndex;
 use crate::tasks::TasksObjective;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct GameState {
     hands: Hands,
     tasks: TasksObjective,
@@ -18,35 +22,191 @@ impl GameState {
             curr_leader,
         }
     }
+
+    pub fn hands(&self) -> &Hands {
+        &self.hands
+    }
+
+    pub fn tasks(&self) -> &TasksObjective {
+        &self.tasks
+    }
+
+    pub fn curr_leader(&self) -> PlayerIndex {
+        self.curr_leader
+    }
+
+    pub fn plays(&self) -> PlayGenerator {
+        PlayGenerator::new(&self.hands, self.curr_leader)
+    }
+
+    /// The card each seat played in `play`, in play order starting with the leader.
+    pub fn cards_by_seat(&self, play: &Play) -> Vec<(PlayerIndex, Card)> {
+        let seats = self.hands.len();
+        (0..seats)
+            .map(|i| ((self.curr_leader as usize + i) % seats) as PlayerIndex)
+            .filter_map(|seat| {
+                let card = *(self.hands[seat as usize] & play.cards()).cards().first()?;
+                Some((seat, card))
+            })
+            .collect()
+    }
+
+    pub fn trick_winner(&self, play: &Play) -> PlayerIndex {
+        find_player_with_card(&self.hands, play.get_trick_winner()).unwrap()
+    }
+
+    /// Every task has been completed.
+    pub fn is_won(&self) -> bool {
+        self.tasks.is_complete()
+    }
+
+    /// Plays a whole trick and checks it against the tasks. Returns `None` if a task was failed
+    /// or can no longer be completed.
+    pub fn after_trick(&self, play: &Play) -> Option<GameState> {
+        let winner = self.trick_winner(play);
+        let remaining_hands = get_remaining_hands(&self.hands, play);
+        let new_tasks = self.tasks.check(play.cards(), winner, &remaining_hands)?;
+        Some(GameState::new(remaining_hands, new_tasks, winner))
+    }
 }
 
 pub fn solve(state: &GameState) -> bool {
-    let generator = PlayGenerator::new(&state.hands, state.curr_leader);
-    for play in generator {
-        let result = solve_play(state, &play);
-        if result {
-            return result;
+    Search::new(None).solve(state).unwrap_or(false)
+}
+
+/// Like `solve`, but gives up and returns `None` once the timeout has passed.
+pub fn solve_within(state: &GameState, timeout: Duration) -> Option<bool> {
+    Search::new(Some(Instant::now() + timeout)).solve(state)
+}
+
+pub struct PlayResults {
+    /// Every play of the next trick, and whether the game can still be won after it.
+    pub plays: Vec<(Play, bool)>,
+    /// Number of tricks looked at during the search.
+    pub nodes: u64,
+}
+
+/// Solves every play of the next trick separately, giving up once the timeout has passed.
+pub fn solve_each_play(state: &GameState, timeout: Duration) -> Option<PlayResults> {
+    Search::new(Some(Instant::now() + timeout)).solve_each_play(state)
+}
+
+/// How many positions are searched between checks of the clock and of cancellation.
+const DEADLINE_CHECK_INTERVAL: u64 = 1 << 12;
+
+/// How many lost positions one search remembers by default. With the hash table's overhead each
+/// takes about 650 bytes, so a full cache is around 170 MB. A quarter of this makes a 7 task
+/// mission take twice as long to solve, and a sixteenth makes it many times slower. Searches run
+/// in parallel share it out with `cache_limit_per_thread`.
+pub const DEFAULT_CACHE_LIMIT: usize = 1 << 18;
+
+/// The cache limit for each of `threads` searches running at once, so that together they
+/// remember no more than `limit` positions.
+pub fn cache_limit_per_thread(limit: usize, threads: usize) -> usize {
+    limit / threads.max(1)
+}
+
+/// A depth-first search over whole tricks that remembers positions it has already seen lost.
+/// Reusing one search for related positions shares that memory.
+pub struct Search {
+    deadline: Option<Instant>,
+    cancelled: Option<Arc<AtomicBool>>,
+    nodes: u64,
+    lost: HashSet<GameState>,
+    cache_limit: usize,
+}
+
+impl Search {
+    /// A search that gives up once `deadline` has passed.
+    pub fn new(deadline: Option<Instant>) -> Search {
+        Search {
+            deadline,
+            cancelled: None,
+            nodes: 0,
+            lost: HashSet::new(),
+            cache_limit: DEFAULT_CACHE_LIMIT,
         }
     }
-    false
-}
 
-fn solve_play(state: &GameState, play: &Play) -> bool {
-    let winning_card = play.get_trick_winner();
-    let winner = find_player_with_card(&state.hands, winning_card).unwrap();
-
-    let new_tasks = state.tasks.check(play.cards(), winner);
-
-    match new_tasks {
-        None => false,
-        Some(new_tasks) => {
-            if new_tasks.is_complete() {
-                true
-            } else {
-                let remaining_hands = get_remaining_hands(&state.hands, play);
-                let new_state = GameState::new(remaining_hands, new_tasks, winner);
-                solve(&new_state)
+    /// Remembers at most `limit` lost positions instead of `DEFAULT_CACHE_LIMIT`, forgetting them
+    /// all and starting over whenever the cache is full. Zero turns the cache off.
+    pub fn with_cache_limit(self, limit: usize) -> Search {
+        Search {
+            cache_limit: limit,
+            ..self
+        }
+    }
+
+    /// Gives up, as if the deadline had passed, once `cancelled` is set from another thread.
+    pub fn with_cancel(self, cancelled: Arc<AtomicBool>) -> Search {
+        Search {
+            cancelled: Some(cancelled),
+            ..self
+        }
+    }
+
+    fn out_of_time(&mut self) -> bool {
+        self.nodes += 1;
+        if !self.nodes.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+            return false;
+        }
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+            || self
+                .cancelled
+                .as_ref()
+                .is_some_and(|c| c.load(Ordering::Relaxed))
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Whether the crew can still win from `state`, or `None` if the deadline passed.
+    pub fn solve(&mut self, state: &GameState) -> Option<bool> {
+        if self.lost.contains(state) {
+            return Some(false);
+        }
+        for play in state.plays() {
+            let result = self.solve_play(state, &play)?;
+            if result {
+                return Some(result);
+            }
+        }
+        if self.cache_limit > 0 {
+            if self.lost.len() >= self.cache_limit {
+                self.lost.clear();
             }
+            self.lost.insert(state.clone());
+        }
+        Some(false)
+    }
+
+    /// Solves every play of the next trick separately, sharing this search's cache.
+    pub fn solve_each_play(mut self, state: &GameState) -> Option<PlayResults> {
+        let plays = state
+            .plays()
+            .map(|play| {
+                let result = self.solve_play(state, &play)?;
+                Some((play, result))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(PlayResults {
+            plays,
+            nodes: self.nodes,
+        })
+    }
+
+    /// Whether the crew can still win after playing `play` as the next trick.
+    pub fn solve_play(&mut self, state: &GameState, play: &Play) -> Option<bool> {
+        if self.out_of_time() {
+            return None;
+        }
+
+        match state.after_trick(play) {
+            None => Some(false),
+            Some(new_state) if new_state.is_won() => Some(true),
+            Some(new_state) => self.solve(&new_state),
         }
     }
 }
@@ -63,3 +223,136 @@ fn find_player_with_card(hands: &Hands, card: CardSet) -> Option<PlayerIndex> {
 fn get_remaining_hands(hands: &Hands, play: &Play) -> Hands {
     hands.map(|x| x & !play.cards())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::{dealt_state, toy_state};
+    use crate::tasks::Task;
+
+    #[test]
+    fn test_solve() {
+        assert!(solve(&toy_state(Task::new(0, Y2))));
+        assert!(solve(&toy_state(Task::new(0, B9))));
+        assert!(!solve(&toy_state(Task::new(1, Y7))));
+    }
+
+    #[test]
+    fn test_solve_within() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            solve_within(&toy_state(Task::new(0, Y2)), timeout),
+            Some(true)
+        );
+        assert_eq!(
+            solve_within(&toy_state(Task::new(1, Y7)), timeout),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_lost_positions() {
+        let state = toy_state(Task::new(1, Y7));
+        let mut search = Search::new(None);
+        assert_eq!(search.solve(&state), Some(false));
+        assert!(search.lost.contains(&state));
+
+        // A lost position is only searched once.
+        let nodes = search.nodes;
+        assert_eq!(search.solve(&state), Some(false));
+        assert_eq!(search.nodes, nodes);
+    }
+
+    #[test]
+    fn test_after_trick() {
+        let state = toy_state(Task::new(0, B9));
+        let plays = state.plays().collect::<Vec<_>>();
+
+        let next = state.after_trick(&plays[0]).unwrap();
+        assert_eq!(state.trick_winner(&plays[0]), 3);
+        assert_eq!(next.curr_leader(), 3);
+        assert!(!next.is_won());
+        assert_eq!(next.hands()[0], CardSet::from_cards(&[B9]));
+
+        let last = plays.last().unwrap();
+        assert_eq!(state.trick_winner(last), 0);
+        assert!(state.after_trick(last).unwrap().is_won());
+    }
+
+    #[test]
+    fn test_cards_by_seat() {
+        let state = GameState::new(
+            *toy_state(Task::new(0, B9)).hands(),
+            TasksObjective::new(&[], &[], &[], None),
+            2,
+        );
+        assert_eq!(
+            state.cards_by_seat(&Play::new(&[B1, B2, Y3, B4])),
+            vec![(2, Y3), (3, B4), (0, B1), (1, B2)]
+        );
+    }
+
+    #[test]
+    fn test_solve_each_play() {
+        let results =
+            solve_each_play(&toy_state(Task::new(0, Y2)), Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            results
+                .plays
+                .iter()
+                .map(|(_, won)| *won)
+                .collect::<Vec<_>>(),
+            vec![false, false, true, true]
+        );
+        assert!(results.nodes >= 4);
+    }
+
+    #[test]
+    fn test_cancel() {
+        // No one but the holder of R4 can win it, but proving that takes a long search.
+        let deal = dealt_state(0, 0);
+        let holder = deal.hands().iter().position(|h| h.contains(R4)).unwrap();
+        let state = GameState::new(
+            *deal.hands(),
+            TasksObjective::new(&[], &[], &[Task::new((holder as u8 + 1) % 4, R4)], None),
+            deal.curr_leader(),
+        );
+        let cancelled = Arc::new(AtomicBool::new(true));
+        let mut search = Search::new(None).with_cancel(cancelled.clone());
+        assert_eq!(search.solve(&state), None);
+        assert_eq!(search.nodes(), DEADLINE_CHECK_INTERVAL);
+
+        cancelled.store(false, Ordering::Relaxed);
+        let mut search = Search::new(None).with_cancel(cancelled);
+        assert_eq!(search.solve(&toy_state(Task::new(0, Y2))), Some(true));
+    }
+
+    #[test]
+    fn test_cache_limit() {
+        for task in [Task::new(0, Y2), Task::new(1, Y7)] {
+            let mut cached = Search::new(None);
+            let mut uncached = Search::new(None).with_cache_limit(0);
+            assert_eq!(
+                cached.solve(&toy_state(task)),
+                uncached.solve(&toy_state(task))
+            );
+            assert!(uncached.lost.is_empty());
+            assert!(cached.nodes() <= uncached.nodes());
+        }
+    }
+
+    #[test]
+    fn test_full_cache() {
+        let (first, second) = (toy_state(Task::new(1, Y7)), toy_state(Task::new(1, B9)));
+        let mut search = Search::new(None).with_cache_limit(1);
+        assert_eq!(search.solve(&first), Some(false));
+        assert_eq!(search.solve(&second), Some(false));
+        // The full cache was cleared to make room.
+        assert_eq!(search.lost.len(), 1);
+        assert!(search.lost.contains(&second));
+
+        assert_eq!(cache_limit_per_thread(1000, 4), 250);
+        assert_eq!(cache_limit_per_thread(1000, 0), 1000);
+    }
+}
@@ -1,40 +1,117 @@
 use crate::card::{Card, CardSet};
+use crate::play::Hands;
 use crate::player::PlayerIndex;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// Most tasks a game can have.
+pub const MAX_TASKS: usize = 12;
+
+/// Tricks in a game, with the whole deck dealt.
+pub const NUM_TRICKS: u8 = 10;
+
+const NO_EARLIEST: u8 = 0;
+const NO_LATEST: u8 = u8::MAX;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Task {
     player: PlayerIndex,
     card: Card,
+    earliest: u8,
+    latest: u8,
 }
 
 impl Task {
     pub fn new(player: PlayerIndex, card: Card) -> Task {
-        Task { player, card }
+        Task {
+            player,
+            card,
+            earliest: NO_EARLIEST,
+            latest: NO_LATEST,
+        }
+    }
+
+    /// Restricts the task to be completed between the given trick numbers (inclusive, counting
+    /// from 1).
+    pub fn with_window(self, earliest: Option<u8>, latest: Option<u8>) -> Task {
+        Task {
+            earliest: earliest.unwrap_or(NO_EARLIEST),
+            latest: latest.unwrap_or(NO_LATEST),
+            ..self
+        }
+    }
+
+    pub fn player(&self) -> PlayerIndex {
+        self.player
+    }
+
+    pub fn card(&self) -> Card {
+        self.card
+    }
+
+    pub fn earliest(&self) -> Option<u8> {
+        (self.earliest != NO_EARLIEST).then_some(self.earliest)
+    }
+
+    pub fn latest(&self) -> Option<u8> {
+        (self.latest != NO_LATEST).then_some(self.latest)
     }
 
     fn matches(&self, play: CardSet) -> bool {
         play.contains(self.card)
     }
 
-    fn evaluate(&self, play: CardSet, winner: PlayerIndex) -> TaskEvaluation {
+    fn in_window(&self, trick: u8) -> bool {
+        self.earliest <= trick && trick <= self.latest
+    }
+
+    /// Whether the card can still be played in the window once `trick` is over, with `hands` the
+    /// cards left. Its holder plays it within as many tricks as they have cards, and if no one
+    /// holds it, the game ends once the longest hand runs out.
+    pub fn can_reach_window(&self, trick: u8, hands: &Hands) -> bool {
+        let cards_left = |hand: &CardSet| hand.num_set() as u8;
+        let tricks_left = match hands.iter().find(|h| h.contains(self.card)) {
+            Some(holder) => cards_left(holder),
+            None => hands.iter().map(cards_left).max().unwrap_or(0),
+        };
+        trick.saturating_add(tricks_left) >= self.earliest
+    }
+
+    /// Checks the task against `trick`, with `hands` the cards left after it.
+    fn evaluate(
+        &self,
+        play: CardSet,
+        winner: PlayerIndex,
+        trick: u8,
+        hands: &Hands,
+    ) -> TaskEvaluation {
         if self.matches(play) {
-            if self.player == winner {
+            if self.player == winner && self.in_window(trick) {
                 TaskEvaluation::Complete
             } else {
                 TaskEvaluation::Failed
             }
+        } else if trick >= self.latest || !self.can_reach_window(trick, hands) {
+            TaskEvaluation::Failed
         } else {
             TaskEvaluation::InProgress
         }
     }
 }
 
-#[derive(PartialEq, Debug)]
+/// Ordering token placed next to a task card. Tasks without a token can be completed at any time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token {
+    Absolute(u8),
+    Relative(u8),
+    Last,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct TasksObjective {
     absolute_tasks: TaskList,
     relative_tasks: TaskList,
     anytime_tasks: TaskList,
     last_task: Option<Task>,
+    tricks_played: u8,
 }
 
 impl TasksObjective {
@@ -49,16 +126,89 @@ impl TasksObjective {
             relative_tasks: TaskListBuilder::from_list(relative_tasks).done(),
             anytime_tasks: TaskListBuilder::from_list(anytime_tasks).done(),
             last_task,
+            tricks_played: 0,
+        }
+    }
+
+    /// Builds an objective from tasks tagged with their ordering tokens. Fails if more than one
+    /// task has the last-trick token or there are more than `MAX_TASKS` tasks.
+    pub fn from_tokens(tasks: &[(Task, Option<Token>)]) -> Option<Self> {
+        if tasks.len() > MAX_TASKS {
+            return None;
+        }
+        let mut absolute = vec![];
+        let mut relative = vec![];
+        let mut anytime = vec![];
+        let mut last = None;
+        for (task, token) in tasks {
+            match token {
+                Some(Token::Absolute(order)) => absolute.push((*order, *task)),
+                Some(Token::Relative(order)) => relative.push((*order, *task)),
+                Some(Token::Last) => {
+                    if last.replace(*task).is_some() {
+                        return None;
+                    }
+                }
+                None => anytime.push(*task),
+            }
         }
+        absolute.sort_by_key(|(order, _)| *order);
+        relative.sort_by_key(|(order, _)| *order);
+
+        let absolute = absolute.into_iter().map(|(_, t)| t).collect::<Vec<_>>();
+        let relative = relative.into_iter().map(|(_, t)| t).collect::<Vec<_>>();
+        Some(Self::new(&absolute, &relative, &anytime, last))
+    }
+
+    /// Sets how many tricks were already played before this objective started being tracked, so
+    /// that trick windows line up with the real trick numbers.
+    pub fn with_tricks_played(self, tricks_played: u8) -> Self {
+        Self {
+            tricks_played,
+            ..self
+        }
+    }
+
+    pub fn tricks_played(&self) -> u8 {
+        self.tricks_played
+    }
+
+    /// The tasks still to be completed, tagged with their ordering tokens. This is the inverse of
+    /// `from_tokens`, with the token orders renumbered from 1.
+    pub fn to_tokens(&self) -> Vec<(Task, Option<Token>)> {
+        let absolute = self.absolute_tasks.valid_tasks().iter().enumerate();
+        let relative = self.relative_tasks.valid_tasks().iter().enumerate();
+
+        absolute
+            .map(|(i, t)| (*t, Some(Token::Absolute(i as u8 + 1))))
+            .chain(relative.map(|(i, t)| (*t, Some(Token::Relative(i as u8 + 1)))))
+            .chain(self.anytime_tasks.valid_tasks().iter().map(|t| (*t, None)))
+            .chain(self.last_task.map(|t| (t, Some(Token::Last))))
+            .collect()
     }
 
-    pub fn check(&self, play: CardSet, winner: PlayerIndex) -> Option<TasksObjective> {
+    /// Checks the tasks against a trick `winner` won, with `hands` the cards left after it.
+    /// Returns `None` if a task was failed or can no longer be completed.
+    pub fn check(
+        &self,
+        play: CardSet,
+        winner: PlayerIndex,
+        hands: &Hands,
+    ) -> Option<TasksObjective> {
+        let trick = self.tricks_played.saturating_add(1);
         if self.check_order(play) {
             Some(TasksObjective {
-                absolute_tasks: self.absolute_tasks.check_completed_front(play, winner)?,
-                relative_tasks: self.relative_tasks.check_completed_front(play, winner)?,
-                anytime_tasks: self.anytime_tasks.check_completed_any(play, winner)?,
-                last_task: self.check_completed_last(play, winner)?,
+                absolute_tasks: self
+                    .absolute_tasks
+                    .check_completed_front(play, winner, trick, hands)?,
+                relative_tasks: self
+                    .relative_tasks
+                    .check_completed_front(play, winner, trick, hands)?,
+                anytime_tasks: self
+                    .anytime_tasks
+                    .check_completed_any(play, winner, trick, hands)?,
+                last_task: self.check_completed_last(play, winner, trick, hands)?,
+                tricks_played: trick,
             })
         } else {
             None
@@ -89,9 +239,15 @@ impl TasksObjective {
         }
     }
 
-    fn check_completed_last(&self, play: CardSet, winner: PlayerIndex) -> Option<Option<Task>> {
+    fn check_completed_last(
+        &self,
+        play: CardSet,
+        winner: PlayerIndex,
+        trick: u8,
+        hands: &Hands,
+    ) -> Option<Option<Task>> {
         match &self.last_task {
-            Some(task) => match task.evaluate(play, winner) {
+            Some(task) => match task.evaluate(play, winner, trick, hands) {
                 TaskEvaluation::Complete => Some(None),
                 TaskEvaluation::Failed => None,
                 TaskEvaluation::InProgress => Some(self.last_task),
@@ -114,9 +270,9 @@ enum TaskEvaluation {
     Complete,
 }
 
-const TASK_LIST_LENGTH: usize = 12;
+const TASK_LIST_LENGTH: usize = MAX_TASKS;
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct TaskList {
     mask: CardSet,
     tasks: [Task; TASK_LIST_LENGTH],
@@ -136,12 +292,18 @@ impl TaskList {
         &self.tasks[..num_tasks]
     }
 
-    fn check_completed_front(&self, play: CardSet, winner: PlayerIndex) -> Option<TaskList> {
+    fn check_completed_front(
+        &self,
+        play: CardSet,
+        winner: PlayerIndex,
+        trick: u8,
+        hands: &Hands,
+    ) -> Option<TaskList> {
         let mut builder = TaskListBuilder::new();
         let mut no_more = false;
         for task in self.valid_tasks() {
             if !no_more {
-                match task.evaluate(play, winner) {
+                match task.evaluate(play, winner, trick, hands) {
                     TaskEvaluation::Complete => {}
                     TaskEvaluation::Failed => {
                         return None;
@@ -152,7 +314,7 @@ impl TaskList {
                     }
                 }
             } else {
-                match task.evaluate(play, winner) {
+                match task.evaluate(play, winner, trick, hands) {
                     TaskEvaluation::Complete => {
                         return None;
                     }
@@ -168,10 +330,16 @@ impl TaskList {
         Some(builder.done())
     }
 
-    fn check_completed_any(&self, play: CardSet, winner: PlayerIndex) -> Option<TaskList> {
+    fn check_completed_any(
+        &self,
+        play: CardSet,
+        winner: PlayerIndex,
+        trick: u8,
+        hands: &Hands,
+    ) -> Option<TaskList> {
         let mut builder = TaskListBuilder::new();
         for task in self.valid_tasks() {
-            match task.evaluate(play, winner) {
+            match task.evaluate(play, winner, trick, hands) {
                 TaskEvaluation::Complete => {}
                 TaskEvaluation::Failed => {
                     return None;
@@ -191,6 +359,12 @@ pub struct TaskListBuilder {
     list: TaskList,
 }
 
+impl Default for TaskListBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TaskListBuilder {
     pub fn new() -> TaskListBuilder {
         TaskListBuilder {
@@ -225,6 +399,10 @@ impl TaskListBuilder {
 mod tests {
     use super::*;
     use crate::card::Card::*;
+    use crate::player::NUM_PLAYERS;
+
+    /// Hands with cards to spare, so that every trick window is still in reach.
+    const LONG_HANDS: Hands = [CardSet::EVERYTHING; NUM_PLAYERS];
 
     #[test]
     fn test_task_list_builder() {
@@ -264,19 +442,19 @@ mod tests {
     #[test]
     fn test_different_players_win() {
         assert!(TasksObjective::new(&[Task::new(0, B8)], &[], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
             .unwrap()
             .is_complete());
         assert!(TasksObjective::new(&[Task::new(1, B8)], &[], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1, &LONG_HANDS)
             .unwrap()
             .is_complete());
         assert!(TasksObjective::new(&[Task::new(2, B8)], &[], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 2)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 2, &LONG_HANDS)
             .unwrap()
             .is_complete());
         assert!(TasksObjective::new(&[Task::new(3, B8)], &[], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 3)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 3, &LONG_HANDS)
             .unwrap()
             .is_complete());
     }
@@ -284,10 +462,10 @@ mod tests {
     #[test]
     fn test_wrong_player_doesnt_win() {
         assert!(TasksObjective::new(&[Task::new(0, B8)], &[], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1, &LONG_HANDS)
             .is_none());
         assert!(TasksObjective::new(&[Task::new(1, B8)], &[], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
             .is_none());
     }
 
@@ -297,12 +475,12 @@ mod tests {
         assert!(!obj.is_complete());
 
         let obj = obj
-            .check(CardSet::from_cards(&[B1, B5, B7, M3]), 0)
+            .check(CardSet::from_cards(&[B1, B5, B7, M3]), 0, &LONG_HANDS)
             .unwrap();
         assert!(!obj.is_complete());
 
         let obj = obj
-            .check(CardSet::from_cards(&[R3, Y8, G2, B8]), 0)
+            .check(CardSet::from_cards(&[R3, Y8, G2, B8]), 0, &LONG_HANDS)
             .unwrap();
         assert!(obj.is_complete());
     }
@@ -310,44 +488,44 @@ mod tests {
     #[test]
     fn test_single_absolute_task() {
         assert!(TasksObjective::new(&[Task::new(0, B8)], &[], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
             .unwrap()
             .is_complete());
         assert!(TasksObjective::new(&[Task::new(0, B8)], &[], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1, &LONG_HANDS)
             .is_none());
     }
 
     #[test]
     fn test_single_relative_task() {
         assert!(TasksObjective::new(&[], &[Task::new(0, B8)], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
             .unwrap()
             .is_complete());
         assert!(TasksObjective::new(&[], &[Task::new(0, B8)], &[], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1, &LONG_HANDS)
             .is_none());
     }
 
     #[test]
     fn test_single_anytime_task() {
         assert!(TasksObjective::new(&[], &[], &[Task::new(0, B8)], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
             .unwrap()
             .is_complete());
         assert!(TasksObjective::new(&[], &[], &[Task::new(0, B8)], None)
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1, &LONG_HANDS)
             .is_none());
     }
 
     #[test]
     fn test_single_last_task() {
         assert!(TasksObjective::new(&[], &[], &[], Some(Task::new(0, B8)))
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
             .unwrap()
             .is_complete());
         assert!(TasksObjective::new(&[], &[], &[], Some(Task::new(0, B8)))
-            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 1, &LONG_HANDS)
             .is_none());
     }
 
@@ -355,15 +533,15 @@ mod tests {
     fn test_two_absolute_tasks() {
         assert!(
             TasksObjective::new(&[Task::new(0, B8), Task::new(1, G1)], &[], &[], None)
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
         assert!(
             TasksObjective::new(&[Task::new(0, B8), Task::new(1, G1)], &[], &[], None)
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .is_none()
         );
     }
@@ -372,15 +550,15 @@ mod tests {
     fn test_two_relative_tasks() {
         assert!(
             TasksObjective::new(&[], &[Task::new(0, B8), Task::new(1, G1)], &[], None)
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
         assert!(
             TasksObjective::new(&[], &[Task::new(0, B8), Task::new(1, G1)], &[], None)
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .is_none()
         );
     }
@@ -389,17 +567,17 @@ mod tests {
     fn test_two_anytime_tasks() {
         assert!(
             TasksObjective::new(&[], &[], &[Task::new(0, B8), Task::new(1, G1)], None)
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
         assert!(
             TasksObjective::new(&[], &[], &[Task::new(0, B8), Task::new(1, G1)], None)
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -409,15 +587,15 @@ mod tests {
     fn test_absolute_before_relative() {
         assert!(
             TasksObjective::new(&[Task::new(0, B8)], &[Task::new(1, G1)], &[], None)
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
         assert!(
             TasksObjective::new(&[Task::new(0, B8)], &[Task::new(1, G1)], &[], None)
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .is_none()
         );
     }
@@ -426,15 +604,15 @@ mod tests {
     fn test_absolute_before_anytime() {
         assert!(
             TasksObjective::new(&[Task::new(0, B8)], &[], &[Task::new(1, G1)], None)
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
         assert!(
             TasksObjective::new(&[Task::new(0, B8)], &[], &[Task::new(1, G1)], None)
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .is_none()
         );
     }
@@ -443,15 +621,15 @@ mod tests {
     fn test_absolute_before_last() {
         assert!(
             TasksObjective::new(&[Task::new(0, B8)], &[], &[], Some(Task::new(1, G1)))
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
         assert!(
             TasksObjective::new(&[Task::new(0, B8)], &[], &[], Some(Task::new(1, G1)))
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .is_none()
         );
     }
@@ -460,17 +638,17 @@ mod tests {
     fn test_relative_can_mix_with_anytime() {
         assert!(
             TasksObjective::new(&[], &[Task::new(0, B8)], &[Task::new(1, G1)], None)
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
         assert!(
             TasksObjective::new(&[], &[Task::new(0, B8)], &[Task::new(1, G1)], None)
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -480,15 +658,15 @@ mod tests {
     fn test_relative_before_last() {
         assert!(
             TasksObjective::new(&[], &[Task::new(0, B8)], &[], Some(Task::new(1, G1)))
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
         assert!(
             TasksObjective::new(&[], &[Task::new(0, B8)], &[], Some(Task::new(1, G1)))
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .is_none()
         );
     }
@@ -497,15 +675,15 @@ mod tests {
     fn test_anytime_before_last() {
         assert!(
             TasksObjective::new(&[], &[], &[Task::new(0, B8)], Some(Task::new(1, G1)))
-                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
         assert!(
             TasksObjective::new(&[], &[], &[Task::new(0, B8)], Some(Task::new(1, G1)))
-                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1)
+                .check(CardSet::from_cards(&[M5, G9, Y2, G1]), 1, &LONG_HANDS)
                 .is_none()
         );
     }
@@ -514,7 +692,7 @@ mod tests {
     fn test_two_absolute_one_turn() {
         assert!(
             TasksObjective::new(&[Task::new(0, G1), Task::new(0, B8)], &[], &[], None)
-                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -524,7 +702,7 @@ mod tests {
     fn test_two_relative_one_turn() {
         assert!(
             TasksObjective::new(&[], &[Task::new(0, G1), Task::new(0, B8)], &[], None)
-                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -534,7 +712,7 @@ mod tests {
     fn test_two_anytime_one_turn() {
         assert!(
             TasksObjective::new(&[], &[], &[Task::new(0, G1), Task::new(0, B8)], None)
-                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -544,7 +722,7 @@ mod tests {
     fn test_absolute_and_relative_one_turn() {
         assert!(
             TasksObjective::new(&[Task::new(0, G1)], &[Task::new(0, B8)], &[], None)
-                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -554,7 +732,7 @@ mod tests {
     fn test_absolute_and_anytime_one_turn() {
         assert!(
             TasksObjective::new(&[Task::new(0, G1)], &[], &[Task::new(0, B8)], None)
-                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -564,7 +742,7 @@ mod tests {
     fn test_absolute_and_last_one_turn() {
         assert!(
             TasksObjective::new(&[Task::new(0, G1)], &[], &[], Some(Task::new(0, B8)))
-                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -574,7 +752,7 @@ mod tests {
     fn test_relative_and_anytime_one_turn() {
         assert!(
             TasksObjective::new(&[], &[Task::new(0, G1)], &[Task::new(0, B8)], None)
-                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -584,7 +762,7 @@ mod tests {
     fn test_relative_and_last_one_turn() {
         assert!(
             TasksObjective::new(&[], &[Task::new(0, G1)], &[], Some(Task::new(0, B8)))
-                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -594,7 +772,7 @@ mod tests {
     fn test_anytime_and_last_one_turn() {
         assert!(
             TasksObjective::new(&[], &[], &[Task::new(0, G1)], Some(Task::new(0, B8)))
-                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0)
+                .check(CardSet::from_cards(&[B1, G1, B8, M3]), 0, &LONG_HANDS)
                 .unwrap()
                 .is_complete()
         );
@@ -608,7 +786,7 @@ mod tests {
             &[Task::new(0, Y3)],
             Some(Task::new(0, B8))
         )
-        .check(CardSet::from_cards(&[B8, M7, Y3, G1]), 0)
+        .check(CardSet::from_cards(&[B8, M7, Y3, G1]), 0, &LONG_HANDS)
         .unwrap()
         .is_complete());
     }
@@ -620,16 +798,144 @@ mod tests {
             &[Task::new(2, Y3)],
             Some(Task::new(3, B8))
         )
-        .check(CardSet::from_cards(&[B6, Y8, G2, M1]), 0)
+        .check(CardSet::from_cards(&[B6, Y8, G2, M1]), 0, &LONG_HANDS)
         .unwrap()
-        .check(CardSet::from_cards(&[G1, M2, Y1, G7]), 0)
+        .check(CardSet::from_cards(&[G1, M2, Y1, G7]), 0, &LONG_HANDS)
         .unwrap()
-        .check(CardSet::from_cards(&[M7, R2, G5, M9]), 1)
+        .check(CardSet::from_cards(&[M7, R2, G5, M9]), 1, &LONG_HANDS)
         .unwrap()
-        .check(CardSet::from_cards(&[B7, Y4, R4, Y3]), 2)
+        .check(CardSet::from_cards(&[B7, Y4, R4, Y3]), 2, &LONG_HANDS)
         .unwrap()
-        .check(CardSet::from_cards(&[B1, Y2, B8, Y9]), 3)
+        .check(CardSet::from_cards(&[B1, Y2, B8, Y9]), 3, &LONG_HANDS)
         .unwrap()
         .is_complete());
     }
+
+    #[test]
+    fn test_from_tokens() {
+        assert_eq!(
+            TasksObjective::from_tokens(&[
+                (Task::new(0, B1), Some(Token::Absolute(2))),
+                (Task::new(1, B2), None),
+                (Task::new(2, B3), Some(Token::Absolute(1))),
+                (Task::new(3, B4), Some(Token::Relative(1))),
+                (Task::new(0, B5), Some(Token::Last)),
+            ]),
+            Some(TasksObjective::new(
+                &[Task::new(2, B3), Task::new(0, B1)],
+                &[Task::new(3, B4)],
+                &[Task::new(1, B2)],
+                Some(Task::new(0, B5))
+            ))
+        );
+        assert_eq!(
+            TasksObjective::from_tokens(&[
+                (Task::new(0, B1), Some(Token::Last)),
+                (Task::new(1, B2), Some(Token::Last)),
+            ]),
+            None
+        );
+
+        let tasks = Card::ALL[..MAX_TASKS + 1]
+            .iter()
+            .map(|c| (Task::new(0, *c), None))
+            .collect::<Vec<_>>();
+        assert_eq!(TasksObjective::from_tokens(&tasks), None);
+        assert!(TasksObjective::from_tokens(&tasks[..MAX_TASKS]).is_some());
+    }
+
+    #[test]
+    fn test_to_tokens() {
+        let tasks = vec![
+            (Task::new(2, B3), Some(Token::Absolute(1))),
+            (Task::new(0, B1), Some(Token::Absolute(2))),
+            (Task::new(3, B4), Some(Token::Relative(1))),
+            (Task::new(1, B2).with_window(None, Some(3)), None),
+            (Task::new(0, B5), Some(Token::Last)),
+        ];
+        let objective = TasksObjective::from_tokens(&tasks).unwrap();
+        assert_eq!(objective.to_tokens(), tasks);
+
+        let objective = objective
+            .check(CardSet::from_cards(&[B3, Y1, Y2, Y3]), 2, &LONG_HANDS)
+            .unwrap();
+        assert_eq!(
+            objective.to_tokens()[0],
+            (Task::new(0, B1), Some(Token::Absolute(1)))
+        );
+    }
+
+    #[test]
+    fn test_latest_trick_window() {
+        let task = Task::new(0, B8).with_window(None, Some(2));
+        assert!(TasksObjective::new(&[], &[], &[task], None)
+            .check(CardSet::from_cards(&[B1, B5, B7, M3]), 0, &LONG_HANDS)
+            .unwrap()
+            .check(CardSet::from_cards(&[R3, Y8, G2, B8]), 0, &LONG_HANDS)
+            .unwrap()
+            .is_complete());
+        assert!(TasksObjective::new(&[], &[], &[task], None)
+            .check(CardSet::from_cards(&[B1, B5, B7, M3]), 0, &LONG_HANDS)
+            .unwrap()
+            .check(CardSet::from_cards(&[R3, Y8, G2, Y1]), 0, &LONG_HANDS)
+            .is_none());
+    }
+
+    #[test]
+    fn test_earliest_trick_window() {
+        let task = Task::new(0, B8).with_window(Some(2), None);
+        assert!(TasksObjective::new(&[task], &[], &[], None)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
+            .is_none());
+        assert!(TasksObjective::new(&[task], &[], &[], None)
+            .check(CardSet::from_cards(&[B1, B5, B7, M3]), 0, &LONG_HANDS)
+            .unwrap()
+            .check(CardSet::from_cards(&[R3, Y8, G2, B8]), 0, &LONG_HANDS)
+            .unwrap()
+            .is_complete());
+    }
+
+    #[test]
+    fn test_trick_window_out_of_reach() {
+        let task = Task::new(0, B8).with_window(Some(3), None);
+        let objective = TasksObjective::new(&[], &[], &[task], None);
+        let play = CardSet::from_cards(&[B1, B5, B7, M3]);
+        // P2 has to play B8 in trick 2, before the window opens.
+        let short = [
+            CardSet::from_cards(&[Y1, Y2]),
+            CardSet::from_cards(&[Y3, Y4]),
+            CardSet::from_cards(&[B8]),
+            CardSet::from_cards(&[Y5, Y6]),
+        ];
+        assert!(objective.check(play, 0, &short).is_none());
+        let long = [
+            CardSet::from_cards(&[Y1, Y2]),
+            CardSet::from_cards(&[Y3, Y4]),
+            CardSet::from_cards(&[B8, Y7]),
+            CardSet::from_cards(&[Y5, Y6]),
+        ];
+        assert!(objective.check(play, 0, &long).is_some());
+        // No one holds B8, and the game is over before trick 3.
+        assert!(objective
+            .check(play, 0, &[CardSet::EMPTY; NUM_PLAYERS])
+            .is_none());
+    }
+
+    #[test]
+    fn test_trick_window_with_tricks_played() {
+        let task = Task::new(0, B8).with_window(Some(3), Some(3));
+        assert!(TasksObjective::new(&[task], &[], &[], None)
+            .with_tricks_played(2)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
+            .unwrap()
+            .is_complete());
+        assert!(TasksObjective::new(&[task], &[], &[], None)
+            .with_tricks_played(3)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
+            .is_none());
+        assert!(TasksObjective::new(&[], &[], &[], None)
+            .with_tricks_played(u8::MAX)
+            .check(CardSet::from_cards(&[B1, B5, B8, M3]), 0, &LONG_HANDS)
+            .is_some());
+    }
 }
@@ -0,0 +1,361 @@
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::card::Card;
+use crate::hint::safe_cards_in;
+use crate::human::task_text;
+use crate::player::{PlayerIndex, NUM_PLAYERS};
+use crate::repl::Session;
+use crate::solver::Search;
+
+const CLEAR: &str = "\x1b[2J\x1b[H";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// What the background solver last found out about the game on screen.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Status {
+    NoGame,
+    Solving,
+    Won,
+    Lost,
+    TimedOut,
+    /// The cards the player to move can play, and whether each keeps the game winnable.
+    Solved {
+        seat: PlayerIndex,
+        cards: Vec<(Card, bool)>,
+    },
+}
+
+/// Draws the whole screen: the hands, the trick in progress, the tricks taken so far, the tasks
+/// still to do and the solver's status, then `message` and a prompt. With `seat` only that
+/// player's hand is shown, and the solver only names safe cards when it is their turn.
+pub fn render(
+    session: &Session,
+    seat: Option<PlayerIndex>,
+    status: &Status,
+    message: &str,
+) -> String {
+    let mut lines = vec![];
+    if let (Ok(record), Ok(position)) = (session.record(), session.position()) {
+        let tricks_played = position.state.tasks().tricks_played();
+        lines.push(format!(
+            "{}The Crew, trick {}{}",
+            BOLD,
+            tricks_played + 1,
+            RESET
+        ));
+        lines.push(String::new());
+
+        for (player, hand) in position.state.hands().iter().enumerate() {
+            let player = player as PlayerIndex;
+            let to_move = if player == position.seat() { ">" } else { " " };
+            let you = if seat == Some(player) { " (you)" } else { "" };
+            let cards = match seat {
+                Some(seat) if seat != player => format!("{}{} cards{}", DIM, hand.num_set(), RESET),
+                _ => cards_text(&hand.cards()),
+            };
+            lines.push(format!("{} P{}{}: {}", to_move, player + 1, you, cards));
+        }
+        lines.push(String::new());
+
+        let leader = position.state.curr_leader();
+        lines.push(format!(
+            "Trick: {}",
+            trick_text(leader, &position.current_trick)
+        ));
+        let (complete, _) = record.end();
+        if complete > 0 {
+            lines.push("Taken:".to_string());
+        }
+        for (i, cards) in record.tricks[..complete].iter().enumerate() {
+            let Ok(before) = record.position(i, 0) else {
+                break;
+            };
+            let Ok(after) = record.position(i + 1, 0) else {
+                break;
+            };
+            lines.push(format!(
+                "  {:>2}. {}, won by P{}",
+                before.state.tasks().tricks_played() + 1,
+                trick_text(before.state.curr_leader(), cards),
+                after.state.curr_leader() + 1
+            ));
+        }
+        lines.push(String::new());
+
+        let tasks = position.state.tasks().to_tokens();
+        lines.push(match tasks.is_empty() {
+            true => "No tasks left".to_string(),
+            false => "Tasks left:".to_string(),
+        });
+        for (task, token) in &tasks {
+            lines.push(format!("  {}", task_text(task, *token)));
+        }
+        lines.push(String::new());
+    }
+
+    lines.push(format!("Solver: {}", status_text(status, seat)));
+    if !message.is_empty() {
+        lines.push(message.to_string());
+    }
+    format!("{}{}\n> ", CLEAR, lines.join("\n"))
+}
+
+fn status_text(status: &Status, seat: Option<PlayerIndex>) -> String {
+    match status {
+        Status::NoGame => "no game loaded; use `load FILE` or `deal TEXT`".to_string(),
+        Status::Solving => "solving...".to_string(),
+        Status::Won => "all tasks are done".to_string(),
+        Status::Lost => "the game is lost".to_string(),
+        Status::TimedOut => "timed out".to_string(),
+        Status::Solved { cards, .. } if !cards.iter().any(|(_, safe)| *safe) => {
+            "not winnable".to_string()
+        }
+        Status::Solved { seat: to_move, .. } if seat.is_some_and(|s| s != *to_move) => {
+            "winnable".to_string()
+        }
+        Status::Solved { seat, cards } => {
+            let safe = cards
+                .iter()
+                .filter(|(_, safe)| *safe)
+                .map(|(c, _)| *c)
+                .collect::<Vec<_>>();
+            format!(
+                "winnable; P{} can safely play {}",
+                seat + 1,
+                cards_text(&safe)
+            )
+        }
+    }
+}
+
+/// The trick's cards in play order, each with the seat that played it.
+fn trick_text(leader: PlayerIndex, cards: &[Card]) -> String {
+    if cards.is_empty() {
+        return "-".to_string();
+    }
+    cards
+        .iter()
+        .enumerate()
+        .map(|(i, card)| {
+            let seat = (leader as usize + i) % NUM_PLAYERS;
+            format!("P{} {}", seat + 1, card_text(*card))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|c| card_text(*c))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The card in its suit's colour, with rockets in bold.
+fn card_text(card: Card) -> String {
+    let name = format!("{:?}", card);
+    let colour = match name.chars().next() {
+        Some('B') => "\x1b[34m",
+        Some('Y') => "\x1b[33m",
+        Some('M') => "\x1b[35m",
+        Some('G') => "\x1b[32m",
+        _ => BOLD,
+    };
+    format!("{}{}{}", colour, name, RESET)
+}
+
+enum Event {
+    Line(String),
+    /// A solve finished, with the generation it was started in.
+    Solved(u64, Status),
+    InputClosed,
+}
+
+/// Solves the game on screen on a thread of its own, cancelling the previous solve whenever the
+/// game changes.
+struct Solver {
+    generation: u64,
+    cancelled: Arc<AtomicBool>,
+    events: Sender<Event>,
+    timeout: Duration,
+}
+
+impl Solver {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Starts solving the end of the session's current branch, and returns the status until the
+    /// solve finishes.
+    fn restart(&mut self, session: &Session) -> Status {
+        self.cancel();
+        self.generation += 1;
+        let Ok(position) = session.position() else {
+            return Status::NoGame;
+        };
+        if position.failed_trick.is_some() {
+            return Status::Lost;
+        }
+        if position.state.is_won() {
+            return Status::Won;
+        }
+
+        self.cancelled = Arc::new(AtomicBool::new(false));
+        let search =
+            Search::new(Some(Instant::now() + self.timeout)).with_cancel(self.cancelled.clone());
+        let (events, generation) = (self.events.clone(), self.generation);
+        thread::spawn(move || {
+            let mut search = search;
+            let seat = position.seat();
+            let status =
+                match safe_cards_in(&mut search, &position.state, &position.current_trick, seat) {
+                    Some(cards) => Status::Solved { seat, cards },
+                    None => Status::TimedOut,
+                };
+            // The screen may have closed already.
+            let _ = events.send(Event::Solved(generation, status));
+        });
+        Status::Solving
+    }
+}
+
+/// Runs the session full screen, reading commands as the REPL does from `input` until `quit` or
+/// the end of the input and redrawing the screen after each one and after each solve. Whenever a
+/// card is played, taken back or the game changes, the solve in progress is cancelled and a new
+/// one started, within `timeout`.
+pub fn run<R, W>(
+    mut session: Session,
+    input: R,
+    output: &mut W,
+    seat: Option<PlayerIndex>,
+    timeout: Duration,
+) -> io::Result<()>
+where
+    R: BufRead + Send + 'static,
+    W: Write,
+{
+    let (events, receiver) = mpsc::channel();
+    let lines = events.clone();
+    thread::spawn(move || {
+        for line in input.lines().map_while(Result::ok) {
+            if lines.send(Event::Line(line)).is_err() {
+                return;
+            }
+        }
+        let _ = lines.send(Event::InputClosed);
+    });
+
+    let mut solver = Solver {
+        generation: 0,
+        cancelled: Arc::new(AtomicBool::new(false)),
+        events,
+        timeout,
+    };
+    let mut status = solver.restart(&session);
+    let mut message = "Type cards to play them, or `help` for the commands".to_string();
+    write!(output, "{}", render(&session, seat, &status, &message))?;
+    output.flush()?;
+
+    for event in receiver {
+        match event {
+            Event::Line(line) if matches!(line.trim(), "quit" | "exit") => break,
+            Event::Line(line) => {
+                let before = session.record().ok();
+                message = session
+                    .execute(&line)
+                    .unwrap_or_else(|e| format!("error: {}", e));
+                if session.record().ok() != before {
+                    status = solver.restart(&session);
+                }
+            }
+            Event::Solved(generation, solved) if generation == solver.generation => status = solved,
+            Event::Solved(..) => continue,
+            Event::InputClosed => break,
+        }
+        write!(output, "{}", render(&session, seat, &status, &message))?;
+        output.flush()?;
+    }
+    solver.cancel();
+    writeln!(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+
+    const DEAL: &str =
+        "deal P1: B1 B9 / P2: B2 Y2 / P3: Y3 Y7 / P4: B4 Y4 / tasks: 1>P1:Y2 / lead P1";
+
+    /// The screen without its escape codes.
+    fn plain(screen: &str) -> String {
+        let mut text = String::new();
+        let mut chars = screen.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.find(|c| c.is_ascii_alphabetic());
+            } else {
+                text.push(c);
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn test_render() {
+        let mut session = Session::new(Duration::from_secs(5));
+        let screen = plain(&render(&session, None, &Status::NoGame, ""));
+        assert_eq!(
+            screen,
+            "Solver: no game loaded; use `load FILE` or `deal TEXT`\n> "
+        );
+
+        session.execute(DEAL).unwrap();
+        session.execute("B9 B2 Y3 B4 B1").unwrap();
+        let status = Status::Solved {
+            seat: 1,
+            cards: vec![(Y2, true)],
+        };
+        let screen = plain(&render(&session, None, &status, "P1 wins trick 1"));
+        assert!(screen.starts_with("The Crew, trick 2\n\n  P1: \n> P2: Y2\n  P3: Y7\n  P4: Y4\n"));
+        assert!(screen.contains("Trick: P1 B1\n"));
+        assert!(screen.contains("Taken:\n   1. P1 B9, P2 B2, P3 Y3, P4 B4, won by P1\n"));
+        assert!(screen.contains("Tasks left:\n  P1 wins Y2 (#1)\n"));
+        assert!(screen.ends_with("Solver: winnable; P2 can safely play Y2\nP1 wins trick 1\n> "));
+
+        let screen = plain(&render(&session, Some(0), &status, ""));
+        assert!(screen.contains("  P1 (you): \n> P2: 1 cards\n"));
+        assert!(screen.contains("Solver: winnable\n"));
+    }
+
+    #[test]
+    fn test_run() {
+        let input = format!("{}\nB9 B2 Y3 B4\nhelp\nquit\nB1\n", DEAL).into_bytes();
+        let mut output = vec![];
+        run(
+            Session::new(Duration::from_secs(5)),
+            io::Cursor::new(input),
+            &mut output,
+            None,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        let screens = String::from_utf8(output).unwrap();
+        let screens = screens.split(CLEAR).map(plain).collect::<Vec<_>>();
+        assert!(screens[1].contains("Solver: no game loaded"));
+        assert!(screens.iter().any(|s| s.contains("Solver: solving...")));
+        let last = screens.last().unwrap();
+        assert!(last.contains("won by P1"));
+        assert!(last.contains("load FILE"));
+        assert!(!last.contains("Trick: P1 B1"));
+    }
+}
@@ -0,0 +1,303 @@
+use std::fmt;
+
+use crate::card::{Card, CardSet};
+use crate::hint::legal_cards;
+use crate::play::{Hands, Play};
+use crate::player::{PlayerIndex, NUM_PLAYERS};
+use crate::solver::GameState;
+use crate::tasks::{Task, Token};
+
+/// Something wrong with a recorded trick.
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    /// The trick doesn't have one card from each player.
+    WrongLength { cards: usize },
+    /// The player doesn't hold the card, or it was already played.
+    NotHeld { player: PlayerIndex, card: Card },
+    /// The player played off suit while still holding the suit that was led.
+    DidNotFollowSuit { player: PlayerIndex, card: Card },
+    /// The recorded winner isn't who wins the trick.
+    WrongWinner { claimed: PlayerIndex },
+    /// The trick fails these tasks: they were won by someone else, out of order, or can no longer
+    /// be completed.
+    TaskFailed { tasks: Vec<Task> },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::WrongLength { cards } => {
+                write!(f, "trick has {} cards instead of {}", cards, NUM_PLAYERS)
+            }
+            Problem::NotHeld { player, card } => {
+                write!(f, "P{} doesn't hold {:?}", player + 1, card)
+            }
+            Problem::DidNotFollowSuit { player, card } => write!(
+                f,
+                "P{} played {:?} without following suit",
+                player + 1,
+                card
+            ),
+            Problem::WrongWinner { claimed } => {
+                write!(f, "recorded as won by P{}", claimed + 1)
+            }
+            Problem::TaskFailed { tasks } => {
+                let tasks = tasks
+                    .iter()
+                    .map(|t| format!("P{} {:?}", t.player() + 1, t.card()))
+                    .collect::<Vec<_>>();
+                write!(f, "task failed: {}", tasks.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TrickReport {
+    /// Counting from 1, including tricks played before the record starts.
+    pub number: u8,
+    pub leader: PlayerIndex,
+    /// `None` if the trick isn't legal.
+    pub winner: Option<PlayerIndex>,
+    /// Tasks completed by this trick, with their tokens as numbered at the start of the record.
+    pub completed: Vec<(Task, Option<Token>)>,
+    pub problems: Vec<Problem>,
+}
+
+impl TrickReport {
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Verification {
+    /// One report per trick, up to and including the first illegal one.
+    pub tricks: Vec<TrickReport>,
+    /// Tricks after an illegal one, which can't be checked.
+    pub unchecked: usize,
+    /// Every task was completed and none failed.
+    pub won: bool,
+    /// Every card was played, or the game was won before that.
+    pub complete: bool,
+}
+
+impl Verification {
+    pub fn passed(&self) -> bool {
+        self.unchecked == 0 && self.complete && self.tricks.iter().all(|t| t.passed())
+    }
+}
+
+/// Checks a game record against the rules: each trick (given in play order, starting with the
+/// leader's card) must be legal, its winner must match `winners` where one was recorded, and it
+/// must not fail a task. Once a task has failed, later tricks are still checked for legality.
+pub fn verify(state: &GameState, tricks: &[Vec<Card>], winners: &[PlayerIndex]) -> Verification {
+    let tokens = state.tasks().to_tokens();
+    let mut state = state.clone();
+    let mut failed = false;
+    let mut reports = vec![];
+
+    for (i, cards) in tricks.iter().enumerate() {
+        let number = state.tasks().tricks_played() + 1;
+        let mut report = TrickReport {
+            number,
+            leader: state.curr_leader(),
+            winner: None,
+            completed: vec![],
+            problems: vec![],
+        };
+
+        if let Some(problem) = illegal_card(&state, cards) {
+            report.problems.push(problem);
+            reports.push(report);
+            return Verification {
+                tricks: reports,
+                unchecked: tricks.len() - i - 1,
+                won: false,
+                complete: false,
+            };
+        }
+
+        let play = Play::new(cards);
+        let winner = state.trick_winner(&play);
+        report.winner = Some(winner);
+        if let Some(claimed) = winners.get(i).filter(|w| **w != winner) {
+            report
+                .problems
+                .push(Problem::WrongWinner { claimed: *claimed });
+        }
+
+        let before = state.tasks().to_tokens();
+        let held = state.hands().map(|h| h & !play.cards());
+        state = match state.after_trick(&play) {
+            Some(next) => {
+                let after = next.tasks().to_tokens();
+                report.completed = tokens
+                    .iter()
+                    .filter(|(task, _)| {
+                        before.iter().any(|(t, _)| t == task)
+                            && !after.iter().any(|(t, _)| t == task)
+                    })
+                    .copied()
+                    .collect();
+                next
+            }
+            None => {
+                if !failed {
+                    report.problems.push(Problem::TaskFailed {
+                        tasks: failed_tasks(&before, play.cards(), number, &held),
+                    });
+                }
+                failed = true;
+                GameState::new(
+                    state.hands().map(|h| h & !play.cards()),
+                    state.tasks().clone().with_tricks_played(number),
+                    winner,
+                )
+            }
+        };
+        reports.push(report);
+    }
+
+    let won = !failed && state.is_won();
+    Verification {
+        tricks: reports,
+        unchecked: 0,
+        won,
+        complete: won || state.hands().iter().all(|h| h.num_set() == 0),
+    }
+}
+
+/// The first card of the trick that `PlayGenerator` wouldn't allow.
+fn illegal_card(state: &GameState, cards: &[Card]) -> Option<Problem> {
+    if cards.len() != NUM_PLAYERS {
+        return Some(Problem::WrongLength { cards: cards.len() });
+    }
+
+    for (i, card) in cards.iter().enumerate() {
+        let player = ((state.curr_leader() as usize + i) % NUM_PLAYERS) as PlayerIndex;
+        if !state.hands()[player as usize].contains(*card) {
+            return Some(Problem::NotHeld {
+                player,
+                card: *card,
+            });
+        }
+
+        let held = state.hands().map(|h| h & !CardSet::from_cards(&cards[..i]));
+        let position = GameState::new(held, state.tasks().clone(), state.curr_leader());
+        let legal = legal_cards(&position, &cards[..i], player).unwrap_or_default();
+        if !legal.contains(card) {
+            return Some(Problem::DidNotFollowSuit {
+                player,
+                card: *card,
+            });
+        }
+    }
+    None
+}
+
+/// The tasks to blame for a failed trick: those whose card was in it, or else those whose last
+/// allowed trick has passed or whose window can't be reached with `hands`, the cards left.
+fn failed_tasks(
+    tasks: &[(Task, Option<Token>)],
+    play: CardSet,
+    number: u8,
+    hands: &Hands,
+) -> Vec<Task> {
+    let in_trick = tasks
+        .iter()
+        .map(|(task, _)| *task)
+        .filter(|task| play.contains(task.card()))
+        .collect::<Vec<_>>();
+    if !in_trick.is_empty() {
+        return in_trick;
+    }
+    tasks
+        .iter()
+        .map(|(task, _)| *task)
+        .filter(|task| {
+            task.latest().is_some_and(|latest| latest <= number)
+                || !task.can_reach_window(number, hands)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card::*;
+    use crate::fixtures::toy_state;
+
+    #[test]
+    fn test_won_game() {
+        let task = Task::new(0, Y2);
+        let tricks = [vec![B9, B2, Y3, B4], vec![B1, Y2, Y7, Y4]];
+        let result = verify(&toy_state(task), &tricks, &[0, 0]);
+
+        assert!(result.passed());
+        assert!(result.won);
+        assert_eq!(result.tricks[1].completed, vec![(task, None)]);
+        assert_eq!(result.tricks[1].number, 2);
+    }
+
+    #[test]
+    fn test_failed_task() {
+        let task = Task::new(0, Y2);
+        let tricks = [vec![B1, B2, Y3, B4], vec![Y4, B9, Y2, Y7]];
+        let result = verify(&toy_state(task), &tricks, &[]);
+
+        assert!(!result.won);
+        assert!(result.complete);
+        assert!(result.tricks[0].passed());
+        assert_eq!(result.tricks[1].leader, 3);
+        assert_eq!(
+            result.tricks[1].problems,
+            vec![Problem::TaskFailed { tasks: vec![task] }]
+        );
+    }
+
+    #[test]
+    fn test_wrong_winner() {
+        let tricks = [vec![B9, B2, Y3, B4]];
+        let result = verify(&toy_state(Task::new(0, Y2)), &tricks, &[3]);
+
+        assert_eq!(
+            result.tricks[0].problems,
+            vec![Problem::WrongWinner { claimed: 3 }]
+        );
+        assert_eq!(
+            result.tricks[0].problems[0].to_string(),
+            "recorded as won by P4"
+        );
+        assert!(!result.complete);
+    }
+
+    #[test]
+    fn test_illegal_tricks() {
+        let state = toy_state(Task::new(0, Y2));
+        let result = verify(&state, &[vec![B1, Y2, Y3, B4], vec![]], &[]);
+        assert_eq!(
+            result.tricks[0].problems,
+            vec![Problem::DidNotFollowSuit {
+                player: 1,
+                card: Y2
+            }]
+        );
+        assert_eq!(result.unchecked, 1);
+
+        let result = verify(&state, &[vec![B1, B2, Y4, B4]], &[]);
+        assert_eq!(
+            result.tricks[0].problems,
+            vec![Problem::NotHeld {
+                player: 2,
+                card: Y4
+            }]
+        );
+
+        let result = verify(&state, &[vec![B1, B2]], &[]);
+        assert_eq!(
+            result.tricks[0].problems,
+            vec![Problem::WrongLength { cards: 2 }]
+        );
+    }
+}